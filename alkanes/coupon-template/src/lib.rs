@@ -1,23 +1,37 @@
 use metashrew_support::compat::to_arraybuffer_layout;
-use metashrew_support::index_pointer::KeyValuePointer;
 
 use alkanes_runtime::{
     declare_alkane, message::MessageDispatch, runtime::AlkaneResponder, token::Token,
-    storage::StoragePointer,
 };
 
 use alkanes_support::{
+    cellpack::Cellpack,
     id::AlkaneId,
-    parcel::AlkaneTransfer,
+    parcel::{AlkaneTransfer, AlkaneTransferParcel},
     response::CallResponse,
 };
 
 use anyhow::{anyhow, Result};
-use std::sync::Arc;
+use gamba_outcome::{OutcomeTier, TierBoundaries};
+use gamba_storage::{Cell, Store};
 
+mod storage;
 mod svg_generator;
 use svg_generator::{SvgGenerator, CouponData};
 
+/// Factory opcodes this coupon calls
+const FACTORY_REDEEM_COUPON: u128 = 2;
+const FACTORY_BURN_COUPON: u128 = 6;
+const FACTORY_IS_REGISTERED_COUPON: u128 = 31;
+const FACTORY_GET_COUPON_STATUS: u128 = 37;
+
+/// Lifecycle statuses, matching the factory's registry
+const COUPON_ACTIVE: u8 = 1;
+const COUPON_REDEEMED: u8 = 2;
+const COUPON_BURNED: u8 = 3;
+const COUPON_EXPIRED: u8 = 4;
+const COUPON_VOID: u8 = 5;
+
 /// Trims a u128 value to a String by removing trailing zeros
 pub fn trim(v: u128) -> String {
     let bytes: Vec<u8> = v.to_le_bytes()
@@ -56,16 +70,6 @@ impl TokenName {
     }
 }
 
-/// Returns a StoragePointer for the token name
-fn name_pointer() -> StoragePointer {
-    StoragePointer::from_keyword("/name")
-}
-
-/// Returns a StoragePointer for the token symbol
-fn symbol_pointer() -> StoragePointer {
-    StoragePointer::from_keyword("/symbol")
-}
-
 /// Convert string to u128 for name encoding
 fn string_to_u128(s: &str) -> u128 {
     let bytes = s.as_bytes();
@@ -77,11 +81,29 @@ fn string_to_u128(s: &str) -> u128 {
     result
 }
 
+/// Rebuild a 32-byte hash from the low and high halves it was passed as
+fn join_halves(low: u128, high: u128) -> [u8; 32] {
+    let mut bytes = [0u8; 32];
+    bytes[..16].copy_from_slice(&low.to_le_bytes());
+    bytes[16..].copy_from_slice(&high.to_le_bytes());
+    bytes
+}
+
 #[derive(Default)]
 pub struct CouponToken(());
 
 impl AlkaneResponder for CouponToken {}
 
+impl Store for CouponToken {
+    fn load_raw(&self, key: &str) -> Vec<u8> {
+        self.load(key.as_bytes().to_vec())
+    }
+
+    fn store_raw(&self, key: &str, value: Vec<u8>) {
+        self.store(key.as_bytes().to_vec(), value)
+    }
+}
+
 #[derive(MessageDispatch)]
 enum CouponTokenMessage {
     #[opcode(0)]
@@ -96,6 +118,13 @@ enum CouponTokenMessage {
         creation_block: u128,
         factory_block: u128,
         factory_tx: u128,
+        tier_boundaries: u128,       // Packed TierBoundaries the factory classified with
+        creation_txid_lo: u128,      // Creation txid in internal byte order, low half
+        creation_txid_hi: u128,
+        merkle_root_lo: u128,        // Merkle root the roll was derived from, low half
+        merkle_root_hi: u128,
+        entropy_index: u128,         // Position of the coupon within its transaction
+        success_threshold: u128,     // Threshold the roll had to beat
     },
 
     #[opcode(10)]
@@ -138,6 +167,58 @@ enum CouponTokenMessage {
     #[returns(CallResponse)]
     IsWinner,
 
+    /// Ask the factory whether it minted this coupon, and its lifecycle status
+    #[opcode(20)]
+    #[returns(CallResponse)]
+    VerifyAuthenticity,
+
+    /// Lifecycle status as the factory reports it, falling back to the last
+    /// status this coupon saw: 1 active, 2 redeemed, 3 burned, 4 expired, 5 void
+    #[opcode(21)]
+    #[returns(CallResponse)]
+    GetStatus,
+
+    /// Losing coupon this one replaced through a re-roll
+    #[opcode(22)]
+    #[returns(CallResponse)]
+    GetParent,
+
+    /// Coupons burned to craft this one, empty for rolled coupons
+    #[opcode(23)]
+    #[returns(CallResponse)]
+    GetCraftInputs,
+
+    /// Creation txid, entropy inputs and threshold the roll was derived from
+    #[opcode(24)]
+    #[returns(CallResponse)]
+    GetProvenance,
+
+    /// Redeem the incoming coupon unit with the factory and return the payout
+    #[opcode(30)]
+    #[returns(CallResponse)]
+    Redeem,
+
+    /// Hand the incoming coupon unit to the factory to be burned
+    #[opcode(31)]
+    #[returns(CallResponse)]
+    Burn,
+
+    /// Factory only: record the coupon a re-roll burned to mint this one
+    #[opcode(40)]
+    #[returns(CallResponse)]
+    ImprintParent {
+        parent_block: u128,
+        parent_tx: u128,
+    },
+
+    /// Factory only: append one of the coupons burned to craft this one
+    #[opcode(41)]
+    #[returns(CallResponse)]
+    ImprintCraftInput {
+        input_block: u128,
+        input_tx: u128,
+    },
+
     /// Get the token name
     #[opcode(99)]
     #[returns(CallResponse)]
@@ -166,27 +247,32 @@ enum CouponTokenMessage {
 
 impl Token for CouponToken {
     fn name(&self) -> String {
-        String::from_utf8(name_pointer().get().as_ref().clone())
-            .unwrap_or_else(|_| {
+        match storage::name().get(self) {
+            Ok(Some(name)) => name,
+            _ => {
                 // Safe fallback that doesn't rely on storage that might not be initialized
-                let coupon_id = self.coupon_id_pointer().get_value::<u128>();
-                let is_winner = self.get_is_winner();
+                let coupon_id = self.coupon_id().unwrap_or_default();
+                let is_winner = self.get_is_winner().unwrap_or_default();
                 format!("{} Coupon #{}", if is_winner { "WINNING" } else { "LOSING" }, coupon_id)
-            })
+            }
+        }
     }
 
     fn symbol(&self) -> String {
-        String::from_utf8(symbol_pointer().get().as_ref().clone())
-            .unwrap_or_else(|_| {
+        match storage::symbol().get(self) {
+            Ok(Some(symbol)) => symbol,
+            _ => {
                 // Safe fallback that doesn't rely on storage that might not be initialized
-                let coupon_id = self.coupon_id_pointer().get_value::<u128>();
-                let is_winner = self.get_is_winner();
+                let coupon_id = self.coupon_id().unwrap_or_default();
+                let is_winner = self.get_is_winner().unwrap_or_default();
                 format!("{}-{}", if is_winner { "WIN" } else { "LOSE" }, coupon_id)
-            })
+            }
+        }
     }
 }
 
 impl CouponToken {
+    #[allow(clippy::too_many_arguments)]
     fn initialize(
         &self,
         coupon_id: u128,
@@ -198,31 +284,63 @@ impl CouponToken {
         creation_block: u128,
         factory_block: u128,
         factory_tx: u128,
+        tier_boundaries: u128,
+        creation_txid_lo: u128,
+        creation_txid_hi: u128,
+        merkle_root_lo: u128,
+        merkle_root_hi: u128,
+        entropy_index: u128,
+        success_threshold: u128,
     ) -> Result<CallResponse> {
         let context = self.context()?;
         let mut response = CallResponse::default();
 
         self.observe_initialization()?;
 
+        // Rolls are imprinted as u8 values, so anything wider would be silently truncated
+        for (field, value) in [
+            ("base_xor", base_xor),
+            ("stake_bonus", stake_bonus),
+            ("final_result", final_result),
+            ("success_threshold", success_threshold),
+        ] {
+            if value > u8::MAX as u128 {
+                return Err(anyhow!("{} must be at most {}, got {}", field, u8::MAX, value));
+            }
+        }
+        if is_winner > 1 {
+            return Err(anyhow!("is_winner must be 0 or 1, got {}", is_winner));
+        }
+        if factory_block == 0 && factory_tx == 0 {
+            return Err(anyhow!("Factory ID must not be zero"));
+        }
+        let tier_boundaries = TierBoundaries::from_u128(tier_boundaries)?;
+
         // Set name and symbol based on coupon properties
         let is_winner_bool = is_winner != 0;
         let coupon_type = if is_winner_bool { "WINNING" } else { "LOSING" };
         let name_string = format!("{} Gambling Coupon #{}", coupon_type, coupon_id);
         let symbol_string = format!("{}-{}", if is_winner_bool { "WIN" } else { "LOSE" }, coupon_id);
         
-        name_pointer().set(Arc::new(name_string.as_bytes().to_vec()));
-        symbol_pointer().set(Arc::new(symbol_string.as_bytes().to_vec()));
+        storage::name().set(self, &name_string);
+        storage::symbol().set(self, &symbol_string);
 
         // Store immutable coupon details
         let factory_id = AlkaneId { block: factory_block, tx: factory_tx };
-        self.set_factory_id(&factory_id);
-        self.set_coupon_id(coupon_id);
-        self.set_stake_amount(stake_amount);
-        self.set_base_xor(base_xor as u8);
-        self.set_stake_bonus(stake_bonus as u8);
-        self.set_final_result(final_result as u8);
-        self.set_is_winner(is_winner_bool);
-        self.set_creation_block(creation_block);
+        storage::factory_id().set(self, &factory_id);
+        storage::coupon_id().set(self, &coupon_id);
+        storage::stake_amount().set(self, &stake_amount);
+        storage::base_xor().set(self, &(base_xor as u8));
+        storage::stake_bonus().set(self, &(stake_bonus as u8));
+        storage::final_result().set(self, &(final_result as u8));
+        storage::is_winner().set(self, &is_winner_bool);
+        storage::creation_block().set(self, &creation_block);
+        storage::status().set(self, &COUPON_ACTIVE);
+        storage::tier_boundaries().set(self, &tier_boundaries);
+        storage::creation_txid().set(self, &join_halves(creation_txid_lo, creation_txid_hi));
+        storage::merkle_root().set(self, &join_halves(merkle_root_lo, merkle_root_hi));
+        storage::entropy_index().set(self, &entropy_index);
+        storage::success_threshold().set(self, &(success_threshold as u8));
 
         // Return exactly 1 coupon token
         response.alkanes.0.push(AlkaneTransfer {
@@ -233,73 +351,64 @@ impl CouponToken {
         Ok(response)
     }
 
-    fn determine_coupon_type(&self, final_result: u8, is_winner: bool) -> String {
-        if is_winner {
-            match final_result {
-                250..=255 => "JACKPOT",
-                230..=249 => "BIG WIN",
-                200..=229 => "WIN",
-                170..=199 => "SMALL WIN",
-                _ => "WIN",
-            }
-        } else {
-            "LOSE"
-        }.to_string()
+    fn tier(&self) -> Result<OutcomeTier> {
+        Ok(self
+            .tier_boundaries()?
+            .classify(self.final_result()?, self.get_is_winner()?))
     }
 
     /// Set the token name and symbol (following free-mint pattern)
     fn set_name_and_symbol(&self, name: TokenName, symbol: u128) {
         let name_string: String = name.into();
-        name_pointer()
-            .set(Arc::new(name_string.as_bytes().to_vec()));
-        self.set_string_field(symbol_pointer(), symbol);
+        storage::name().set(self, &name_string);
+        self.set_string_field(storage::symbol(), symbol);
     }
 
     /// Set a string field in storage (following free-mint pattern)
-    fn set_string_field(&self, mut pointer: StoragePointer, v: u128) {
-        pointer.set(Arc::new(trim(v).as_bytes().to_vec()));
+    fn set_string_field(&self, cell: Cell<String>, v: u128) {
+        cell.set(self, &trim(v));
     }
 
     // Getter functions
     fn get_coupon_id(&self) -> Result<CallResponse> {
         let context = self.context()?;
         let mut response = CallResponse::forward(&context.incoming_alkanes);
-        response.data = self.coupon_id().to_le_bytes().to_vec();
+        response.data = self.coupon_id()?.to_le_bytes().to_vec();
         Ok(response)
     }
 
     fn get_stake_amount(&self) -> Result<CallResponse> {
         let context = self.context()?;
         let mut response = CallResponse::forward(&context.incoming_alkanes);
-        response.data = self.stake_amount().to_le_bytes().to_vec();
+        response.data = self.stake_amount()?.to_le_bytes().to_vec();
         Ok(response)
     }
 
     fn get_base_xor(&self) -> Result<CallResponse> {
         let context = self.context()?;
         let mut response = CallResponse::forward(&context.incoming_alkanes);
-        response.data = (self.base_xor() as u128).to_le_bytes().to_vec();
+        response.data = (self.base_xor()? as u128).to_le_bytes().to_vec();
         Ok(response)
     }
 
     fn get_stake_bonus(&self) -> Result<CallResponse> {
         let context = self.context()?;
         let mut response = CallResponse::forward(&context.incoming_alkanes);
-        response.data = (self.stake_bonus() as u128).to_le_bytes().to_vec();
+        response.data = (self.stake_bonus()? as u128).to_le_bytes().to_vec();
         Ok(response)
     }
 
     fn get_final_result(&self) -> Result<CallResponse> {
         let context = self.context()?;
         let mut response = CallResponse::forward(&context.incoming_alkanes);
-        response.data = (self.final_result() as u128).to_le_bytes().to_vec();
+        response.data = (self.final_result()? as u128).to_le_bytes().to_vec();
         Ok(response)
     }
 
     fn get_creation_block(&self) -> Result<CallResponse> {
         let context = self.context()?;
         let mut response = CallResponse::forward(&context.incoming_alkanes);
-        response.data = self.creation_block().to_le_bytes().to_vec();
+        response.data = self.creation_block()?.to_le_bytes().to_vec();
         Ok(response)
     }
 
@@ -307,7 +416,7 @@ impl CouponToken {
         let context = self.context()?;
         let mut response = CallResponse::forward(&context.incoming_alkanes);
         
-        let factory_id = self.factory_ref();
+        let factory_id = self.factory_ref()?;
         
         // Pack AlkaneId into response (32 bytes: 16 for block, 16 for tx)
         let mut data = Vec::with_capacity(32);
@@ -323,13 +432,13 @@ impl CouponToken {
         let mut response = CallResponse::forward(&context.incoming_alkanes);
 
         // Return core coupon details
-        let coupon_id = self.coupon_id();
-        let stake_amount = self.stake_amount();
-        let base_xor = self.base_xor() as u128;
-        let stake_bonus = self.stake_bonus() as u128;
-        let final_result = self.final_result() as u128;
-        let creation_block = self.creation_block();
-        let is_winner = if self.get_is_winner() { 1u128 } else { 0u128 };
+        let coupon_id = self.coupon_id()?;
+        let stake_amount = self.stake_amount()?;
+        let base_xor = self.base_xor()? as u128;
+        let stake_bonus = self.stake_bonus()? as u128;
+        let final_result = self.final_result()? as u128;
+        let creation_block = self.creation_block()?;
+        let is_winner = if self.get_is_winner()? { 1u128 } else { 0u128 };
 
         // Pack all values into a single byte array
         // Each value is 16 bytes (128 bits) - 7 values total
@@ -350,8 +459,7 @@ impl CouponToken {
         let context = self.context()?;
         let mut response = CallResponse::forward(&context.incoming_alkanes);
 
-        let coupon_type = self.determine_coupon_type(self.final_result(), self.get_is_winner());
-        response.data = coupon_type.as_bytes().to_vec();
+        response.data = self.tier()?.name().as_bytes().to_vec();
 
         Ok(response)
     }
@@ -360,124 +468,272 @@ impl CouponToken {
         let context = self.context()?;
         let mut response = CallResponse::forward(&context.incoming_alkanes);
 
-        let is_winner = if self.get_is_winner() { 1u128 } else { 0u128 };
+        let is_winner = if self.get_is_winner()? { 1u128 } else { 0u128 };
         response.data = is_winner.to_le_bytes().to_vec();
 
         Ok(response)
     }
 
-    // Storage operations using StoragePointer pattern
+    fn get_status(&self) -> Result<CallResponse> {
+        let context = self.context()?;
+        let mut response = CallResponse::forward(&context.incoming_alkanes);
+        let status = self.lifecycle_status(&self.factory_verification())?;
+        response.data = (status as u128).to_le_bytes().to_vec();
+        Ok(response)
+    }
+
+    fn get_provenance(&self) -> Result<CallResponse> {
+        let context = self.context()?;
+        let mut response = CallResponse::forward(&context.incoming_alkanes);
+
+        let txid = storage::creation_txid()
+            .get(self)?
+            .ok_or_else(|| anyhow!("Coupon was minted before provenance was recorded"))?;
+
+        let merkle_root = storage::merkle_root().get_or_default(self)?;
+        let entropy_index = storage::entropy_index().get_or_default(self)?;
+        let success_threshold = storage::success_threshold().get_or_default(self)? as u128;
 
-    fn factory_alkane_id_pointer(&self) -> StoragePointer {
-        StoragePointer::from_keyword("/factory-alkane-id")
+        // Format: [txid (32)] + [merkle_root (32)] + [entropy_index (16)]
+        //       + [success_threshold (16)] + [creation_block (16)]
+        let mut data = Vec::with_capacity(112);
+        data.extend_from_slice(&txid);
+        data.extend_from_slice(&merkle_root);
+        data.extend_from_slice(&entropy_index.to_le_bytes());
+        data.extend_from_slice(&success_threshold.to_le_bytes());
+        data.extend_from_slice(&self.creation_block()?.to_le_bytes());
+
+        response.data = data;
+        Ok(response)
     }
 
-    fn factory_ref(&self) -> AlkaneId {
-        let data = self.factory_alkane_id_pointer().get();
-        if data.len() == 0 {
-            panic!("Factory reference not found");
+    fn get_parent(&self) -> Result<CallResponse> {
+        let context = self.context()?;
+        let mut response = CallResponse::forward(&context.incoming_alkanes);
+
+        let parent_id = storage::parent_id()
+            .get(self)?
+            .ok_or_else(|| anyhow!("Coupon was not minted by a re-roll"))?;
+
+        let mut data = Vec::with_capacity(32);
+        data.extend_from_slice(&parent_id.block.to_le_bytes());
+        data.extend_from_slice(&parent_id.tx.to_le_bytes());
+
+        response.data = data;
+        Ok(response)
+    }
+
+    fn imprint_parent(&self, parent_block: u128, parent_tx: u128) -> Result<CallResponse> {
+        let context = self.context()?;
+
+        self.only_factory(&context.caller)?;
+        if storage::parent_id().get(self)?.is_some() {
+            return Err(anyhow!("Parent coupon is already imprinted"));
         }
-        
-        let bytes = data.as_ref();
-        AlkaneId {
-            block: u128::from_le_bytes(bytes[0..16].try_into().unwrap()),
-            tx: u128::from_le_bytes(bytes[16..32].try_into().unwrap()),
+        storage::parent_id().set(self, &AlkaneId { block: parent_block, tx: parent_tx });
+
+        Ok(CallResponse::forward(&context.incoming_alkanes))
+    }
+
+    fn get_craft_inputs(&self) -> Result<CallResponse> {
+        let context = self.context()?;
+        let mut response = CallResponse::forward(&context.incoming_alkanes);
+
+        let inputs = storage::craft_inputs().all(self)?;
+
+        // Format: [count (8 bytes)] + [AlkaneId_1 (32 bytes)] + ... in crafting order
+        let mut data = Vec::with_capacity(8 + inputs.len() * 32);
+        data.extend_from_slice(&(inputs.len() as u64).to_le_bytes());
+        for input in inputs {
+            data.extend_from_slice(&input.block.to_le_bytes());
+            data.extend_from_slice(&input.tx.to_le_bytes());
         }
+
+        response.data = data;
+        Ok(response)
     }
 
-    fn set_factory_id(&self, id: &AlkaneId) {
-        let mut bytes = Vec::with_capacity(32);
-        bytes.extend_from_slice(&id.block.to_le_bytes());
-        bytes.extend_from_slice(&id.tx.to_le_bytes());
-        
-        self.factory_alkane_id_pointer().set(Arc::new(bytes));
+    fn imprint_craft_input(&self, input_block: u128, input_tx: u128) -> Result<CallResponse> {
+        let context = self.context()?;
+
+        self.only_factory(&context.caller)?;
+        storage::craft_inputs().push(self, &AlkaneId { block: input_block, tx: input_tx })?;
+
+        Ok(CallResponse::forward(&context.incoming_alkanes))
     }
 
-    fn coupon_id_pointer(&self) -> StoragePointer {
-        StoragePointer::from_keyword("/coupon_id")
+    /// Lineage is imprinted by the factory after Initialize, so only it may write
+    fn only_factory(&self, caller: &AlkaneId) -> Result<()> {
+        if *caller != self.factory_ref()? {
+            return Err(anyhow!("Only the factory can imprint lineage"));
+        }
+        Ok(())
     }
 
-    fn coupon_id(&self) -> u128 {
-        self.coupon_id_pointer().get_value::<u128>()
+    fn redeem(&self) -> Result<CallResponse> {
+        let factory_response = self.forward_to_factory(FACTORY_REDEEM_COUPON)?;
+        storage::status().set(self, &COUPON_REDEEMED);
+
+        // The factory keeps the coupon unit and pays out to us; pass the payout on
+        let mut response = CallResponse::default();
+        response.alkanes = factory_response.alkanes;
+        response.data = factory_response.data;
+        Ok(response)
     }
 
-    fn set_coupon_id(&self, coupon_id: u128) {
-        self.coupon_id_pointer().set_value::<u128>(coupon_id);
+    fn burn(&self) -> Result<CallResponse> {
+        self.forward_to_factory(FACTORY_BURN_COUPON)?;
+        storage::status().set(self, &COUPON_BURNED);
+
+        Ok(CallResponse::default())
     }
 
-    // Coupon data storage functions
-    fn stake_amount_pointer(&self) -> StoragePointer {
-        StoragePointer::from_keyword("/stake_amount")
+    /// Send the single incoming unit of this coupon to a factory opcode.
+    /// Any factory error reverts the call, which refunds the unit.
+    fn forward_to_factory(&self, opcode: u128) -> Result<CallResponse> {
+        let context = self.context()?;
+
+        let unit = match context.incoming_alkanes.0.as_slice() {
+            [transfer] if transfer.id == context.myself && transfer.value == 1 => transfer.clone(),
+            _ => return Err(anyhow!("Expected exactly one unit of this coupon")),
+        };
+        if self.status()? != COUPON_ACTIVE {
+            return Err(anyhow!("Coupon is no longer active"));
+        }
+
+        let cellpack = Cellpack {
+            target: self.factory_ref()?,
+            inputs: vec![opcode],
+        };
+        self.call(&cellpack, &AlkaneTransferParcel(vec![unit]), self.fuel())
     }
 
-    fn stake_amount(&self) -> u128 {
-        self.stake_amount_pointer().get_value::<u128>()
+    fn verify_authenticity(&self) -> Result<CallResponse> {
+        let context = self.context()?;
+        let mut response = CallResponse::forward(&context.incoming_alkanes);
+
+        // Format: [authentic (16)] + [factory lifecycle status (16)]
+        let (authentic, status) = self.factory_verification()?;
+        let mut data = Vec::with_capacity(32);
+        data.extend_from_slice(&(if authentic { 1u128 } else { 0u128 }).to_le_bytes());
+        data.extend_from_slice(&status.to_le_bytes());
+
+        response.data = data;
+        Ok(response)
     }
 
-    fn set_stake_amount(&self, stake_amount: u128) {
-        self.stake_amount_pointer().set_value::<u128>(stake_amount);
+    /// Look this coupon up in the registry of the factory it claims to come from.
+    /// A look-alike template can imprint any values, but cannot register itself.
+    fn factory_verification(&self) -> Result<(bool, u128)> {
+        let myself = self.context()?.myself;
+        let factory = self.factory_ref()?;
+
+        let registered = self.query_factory(&factory, FACTORY_IS_REGISTERED_COUPON, &myself)?;
+        if registered != 1 {
+            return Ok((false, 0));
+        }
+        let status = self.query_factory(&factory, FACTORY_GET_COUPON_STATUS, &myself)?;
+        Ok((true, status))
     }
 
-    fn base_xor_pointer(&self) -> StoragePointer {
-        StoragePointer::from_keyword("/base_xor")
+    /// Whether the factory vouches for this coupon; any failure to ask counts as no
+    fn is_verified(verification: &Result<(bool, u128)>) -> bool {
+        matches!(verification, Ok((true, _)))
     }
 
-    fn base_xor(&self) -> u8 {
-        self.base_xor_pointer().get_value::<u8>()
+    fn query_factory(&self, factory: &AlkaneId, opcode: u128, coupon_id: &AlkaneId) -> Result<u128> {
+        let cellpack = Cellpack {
+            target: factory.clone(),
+            inputs: vec![opcode, coupon_id.block, coupon_id.tx],
+        };
+
+        let response = self
+            .staticcall(&cellpack, &AlkaneTransferParcel::default(), self.fuel())
+            .map_err(|e| anyhow!("Factory {}:{} did not respond: {}", factory.block, factory.tx, e))?;
+        let value = response
+            .data
+            .get(0..16)
+            .ok_or_else(|| anyhow!("Factory returned {} bytes, expected 16", response.data.len()))?;
+        Ok(u128::from_le_bytes(value.try_into()?))
     }
 
-    fn set_base_xor(&self, base_xor: u8) {
-        self.base_xor_pointer().set_value::<u8>(base_xor);
+    // Storage operations, see the storage module for the layout
+
+    fn factory_ref(&self) -> Result<AlkaneId> {
+        storage::factory_id().require(self)
     }
 
-    fn stake_bonus_pointer(&self) -> StoragePointer {
-        StoragePointer::from_keyword("/stake_bonus")
+    fn coupon_id(&self) -> Result<u128> {
+        storage::coupon_id().get_or_default(self)
     }
 
-    fn stake_bonus(&self) -> u8 {
-        self.stake_bonus_pointer().get_value::<u8>()
+    fn stake_amount(&self) -> Result<u128> {
+        storage::stake_amount().get_or_default(self)
     }
 
-    fn set_stake_bonus(&self, stake_bonus: u8) {
-        self.stake_bonus_pointer().set_value::<u8>(stake_bonus);
+    fn base_xor(&self) -> Result<u8> {
+        storage::base_xor().get_or_default(self)
     }
 
-    fn final_result_pointer(&self) -> StoragePointer {
-        StoragePointer::from_keyword("/final_result")
+    fn stake_bonus(&self) -> Result<u8> {
+        storage::stake_bonus().get_or_default(self)
     }
 
-    fn final_result(&self) -> u8 {
-        self.final_result_pointer().get_value::<u8>()
+    fn final_result(&self) -> Result<u8> {
+        storage::final_result().get_or_default(self)
     }
 
-    fn set_final_result(&self, final_result: u8) {
-        self.final_result_pointer().set_value::<u8>(final_result);
+    fn get_is_winner(&self) -> Result<bool> {
+        storage::is_winner().get_or_default(self)
     }
 
-    fn is_winner_pointer(&self) -> StoragePointer {
-        StoragePointer::from_keyword("/is_winner")
+    fn creation_block(&self) -> Result<u128> {
+        storage::creation_block().get_or_default(self)
     }
 
-    fn get_is_winner(&self) -> bool {
-        self.is_winner_pointer().get_value::<u8>() != 0
+    /// Coupons initialized before boundaries were imprinted use the defaults
+    fn tier_boundaries(&self) -> Result<TierBoundaries> {
+        storage::tier_boundaries().get_or_default(self)
     }
 
-    fn set_is_winner(&self, is_winner: bool) {
-        self.is_winner_pointer().set_value::<u8>(if is_winner { 1 } else { 0 });
+    /// Coupons initialized before the status was tracked count as active
+    fn status(&self) -> Result<u8> {
+        Ok(storage::status().get(self)?.unwrap_or(COUPON_ACTIVE))
     }
 
-    fn creation_block_pointer(&self) -> StoragePointer {
-        StoragePointer::from_keyword("/creation_block")
+    /// The factory's registry is authoritative, since an admin can expire or void
+    /// a coupon without it knowing. The local cell only answers when the factory
+    /// cannot vouch for this coupon.
+    fn lifecycle_status(&self, verification: &Result<(bool, u128)>) -> Result<u8> {
+        match verification {
+            Ok((true, status)) if *status != 0 => u8::try_from(*status)
+                .map_err(|_| anyhow!("Factory reported unknown status {}", status)),
+            _ => self.status(),
+        }
     }
 
-    fn creation_block(&self) -> u128 {
-        self.creation_block_pointer().get_value::<u128>()
+    /// Creation txid in the reversed hex form explorers display
+    fn creation_txid_hex(&self) -> Result<Option<String>> {
+        Ok(storage::creation_txid()
+            .get(self)?
+            .map(|txid| txid.iter().rev().map(|byte| format!("{:02x}", byte)).collect()))
     }
 
-    fn set_creation_block(&self, creation_block: u128) {
-        self.creation_block_pointer().set_value::<u128>(creation_block);
+    /// Number of coupons burned to craft this one, 0 for rolled coupons
+    fn crafted_from(&self) -> Result<u128> {
+        storage::craft_inputs().len(self)
     }
 
+    /// Stamp drawn across coupons that have been spent
+    fn status_stamp(&self, verification: &Result<(bool, u128)>) -> Result<Option<String>> {
+        Ok(match self.lifecycle_status(verification)? {
+            COUPON_REDEEMED => Some("REDEEMED".to_string()),
+            COUPON_BURNED => Some("BURNED".to_string()),
+            COUPON_EXPIRED => Some("EXPIRED".to_string()),
+            COUPON_VOID => Some("VOID".to_string()),
+            _ => None,
+        })
+    }
 
     /// Get the token name (following free-mint pattern)
     fn get_name(&self) -> Result<CallResponse> {
@@ -499,23 +755,35 @@ impl CouponToken {
         Ok(response)
     }
 
+    /// Everything the SVG and attributes are drawn from. The factory is asked
+    /// once, and its answer drives both the watermark and the status stamp.
+    fn coupon_data(&self) -> Result<CouponData> {
+        let verification = self.factory_verification();
+
+        Ok(CouponData {
+            coupon_id: self.coupon_id()?,
+            stake_amount: self.stake_amount()?,
+            base_xor: self.base_xor()?,
+            stake_bonus: self.stake_bonus()?,
+            final_result: self.final_result()?,
+            creation_block: self.creation_block()?,
+            current_block: u128::from(self.height()),
+            is_winner: self.get_is_winner()?,
+            boundaries: self.tier_boundaries()?,
+            verified: Self::is_verified(&verification),
+            status_stamp: self.status_stamp(&verification)?,
+            crafted_from: self.crafted_from()?,
+            creation_txid: self.creation_txid_hex()?,
+        })
+    }
+
     /// Get the SVG data for this coupon token
     fn get_data(&self) -> Result<CallResponse> {
         let context = self.context()?;
         let mut response = CallResponse::forward(&context.incoming_alkanes);
 
         // Prepare coupon data for SVG generation
-        let coupon_data = CouponData {
-            coupon_id: self.coupon_id(),
-            stake_amount: self.stake_amount(),
-            base_xor: self.base_xor(),
-            stake_bonus: self.stake_bonus(),
-            final_result: self.final_result(),
-            creation_block: self.creation_block(),
-            current_block: u128::from(self.height()),
-            coupon_type: self.determine_coupon_type(self.final_result(), self.get_is_winner()),
-            is_winner: self.get_is_winner(),
-        };
+        let coupon_data = self.coupon_data()?;
 
         // Generate the SVG
         let svg = SvgGenerator::generate_svg(coupon_data)?;
@@ -540,17 +808,7 @@ impl CouponToken {
         let mut response = CallResponse::forward(&context.incoming_alkanes);
 
         // Prepare coupon data for attributes generation
-        let coupon_data = CouponData {
-            coupon_id: self.coupon_id(),
-            stake_amount: self.stake_amount(),
-            base_xor: self.base_xor(),
-            stake_bonus: self.stake_bonus(),
-            final_result: self.final_result(),
-            creation_block: self.creation_block(),
-            current_block: u128::from(self.height()),
-            coupon_type: self.determine_coupon_type(self.final_result(), self.get_is_winner()),
-            is_winner: self.get_is_winner(),
-        };
+        let coupon_data = self.coupon_data()?;
 
         // Generate the attributes JSON
         let attributes = SvgGenerator::get_attributes(coupon_data)?;
@@ -0,0 +1,85 @@
+//! Storage layout of a coupon token. Everything but the lifecycle status is
+//! written once, by Initialize or by a factory imprint right after it.
+
+use alkanes_support::id::AlkaneId;
+use gamba_outcome::TierBoundaries;
+use gamba_storage::{Cell, IndexedList};
+
+pub fn name() -> Cell<String> {
+    Cell::new("/name")
+}
+
+pub fn symbol() -> Cell<String> {
+    Cell::new("/symbol")
+}
+
+pub fn factory_id() -> Cell<AlkaneId> {
+    Cell::new("/factory-alkane-id")
+}
+
+pub fn coupon_id() -> Cell<u128> {
+    Cell::new("/coupon_id")
+}
+
+pub fn stake_amount() -> Cell<u128> {
+    Cell::new("/stake_amount")
+}
+
+pub fn base_xor() -> Cell<u8> {
+    Cell::new("/base_xor")
+}
+
+pub fn stake_bonus() -> Cell<u8> {
+    Cell::new("/stake_bonus")
+}
+
+pub fn final_result() -> Cell<u8> {
+    Cell::new("/final_result")
+}
+
+pub fn is_winner() -> Cell<bool> {
+    Cell::new("/is_winner")
+}
+
+pub fn creation_block() -> Cell<u128> {
+    Cell::new("/creation_block")
+}
+
+pub fn tier_boundaries() -> Cell<TierBoundaries> {
+    Cell::new("/tier_boundaries")
+}
+
+/// Lifecycle status as last seen by this coupon, using the factory's codes
+pub fn status() -> Cell<u8> {
+    Cell::new("/status")
+}
+
+/// Coupon a re-roll burned to mint this one, imprinted by the factory
+pub fn parent_id() -> Cell<AlkaneId> {
+    Cell::new("/parent_id")
+}
+
+/// Coupons burned to craft this one, imprinted by the factory in crafting order
+pub fn craft_inputs() -> IndexedList<AlkaneId> {
+    IndexedList::under("/craft_inputs")
+}
+
+// Provenance of the roll, see GetProvenance
+
+/// Creation txid in internal byte order
+pub fn creation_txid() -> Cell<[u8; 32]> {
+    Cell::new("/provenance/txid")
+}
+
+/// Merkle root the roll was derived from; zero for crafted coupons
+pub fn merkle_root() -> Cell<[u8; 32]> {
+    Cell::new("/provenance/merkle_root")
+}
+
+pub fn entropy_index() -> Cell<u128> {
+    Cell::new("/provenance/entropy_index")
+}
+
+pub fn success_threshold() -> Cell<u8> {
+    Cell::new("/provenance/success_threshold")
+}
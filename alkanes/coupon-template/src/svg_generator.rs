@@ -1,4 +1,5 @@
 use anyhow::Result;
+use gamba_outcome::{OutcomeTier, TierBoundaries};
 
 #[derive(Debug, Clone)]
 pub struct CouponData {
@@ -9,8 +10,37 @@ pub struct CouponData {
     pub final_result: u8,
     pub creation_block: u128,
     pub current_block: u128,
-    pub coupon_type: String,
     pub is_winner: bool,
+    /// Boundaries the coupon was classified with; see [`CouponData::tier`]
+    pub boundaries: TierBoundaries,
+    /// Whether the factory confirmed it minted this coupon
+    pub verified: bool,
+    /// Lifecycle stamp for spent coupons, e.g. "REDEEMED"
+    pub status_stamp: Option<String>,
+    /// Number of coupons burned to craft this one, 0 for rolled coupons
+    pub crafted_from: u128,
+    /// Creation txid as explorers display it; unset on coupons minted before provenance
+    pub creation_txid: Option<String>,
+}
+
+impl CouponData {
+    pub fn tier(&self) -> OutcomeTier {
+        self.boundaries.classify(self.final_result, self.is_winner)
+    }
+
+    /// Tier the coupon is drawn as. Crafted coupons never win, so they are
+    /// drawn by the band their crafted score reaches instead.
+    pub fn display_tier(&self) -> OutcomeTier {
+        if self.is_crafted() {
+            self.boundaries.band(self.final_result)
+        } else {
+            self.tier()
+        }
+    }
+
+    pub fn is_crafted(&self) -> bool {
+        self.crafted_from > 0
+    }
 }
 
 pub struct SvgGenerator;
@@ -18,6 +48,9 @@ pub struct SvgGenerator;
 impl SvgGenerator {
     /// Generate an SVG representation of a coupon based on its properties
     pub fn generate_svg(data: CouponData) -> Result<String> {
+        let tier = data.display_tier();
+        let band = data.boundaries.band(data.final_result);
+        let crafted = data.is_crafted();
         let CouponData {
             coupon_id,
             stake_amount,
@@ -26,18 +59,30 @@ impl SvgGenerator {
             final_result,
             creation_block,
             current_block,
-            coupon_type,
             is_winner,
+            verified,
+            status_stamp,
+            crafted_from,
+            creation_txid,
+            ..
         } = data;
 
+        // Crafted coupons get the bright palette of the band they reach
+        let bright = is_winner || crafted;
+
         // Calculate colors based on coupon properties
-        let (primary_color, secondary_color, accent_color) = Self::calculate_colors(final_result, base_xor, is_winner);
-        let ticket_width = Self::calculate_ticket_width(&coupon_type);
-        let badge_size = Self::calculate_badge_size(final_result);
+        let (primary_color, secondary_color, accent_color) = Self::calculate_colors(band, base_xor, bright);
+        let ticket_width = Self::calculate_ticket_width(tier);
+        let badge_size = Self::calculate_badge_size(band, bright);
         let decoration_count = Self::calculate_decoration_count(stake_amount);
 
-        let status_text = if is_winner { "WINNER" } else { "BETTER LUCK NEXT TIME" };
-        let status_color = if is_winner { "#10b981" } else { "#ef4444" };
+        let (status_text, status_color) = if crafted {
+            (format!("CRAFTED FROM {} COUPONS", crafted_from), "#a855f7")
+        } else if is_winner {
+            ("WINNER".to_string(), "#10b981")
+        } else {
+            ("BETTER LUCK NEXT TIME".to_string(), "#ef4444")
+        };
 
         let svg = format!(
             r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 400 600" width="400" height="600">
@@ -115,8 +160,10 @@ impl SvgGenerator {
     XOR: {base_xor} | Bonus: +{stake_bonus}
   </text>
   <text x="70" y="570" font-family="monospace" font-size="10" fill="{gray_color}">
-    Block: {creation_block}
+    Block: {creation_block}{txid_suffix}
   </text>
+  {watermark}
+  {stamp}
 </svg>"#,
             primary_color = primary_color,
             secondary_color = secondary_color,
@@ -134,55 +181,78 @@ impl SvgGenerator {
             glow_size = badge_size + 10,
             decorations = Self::generate_decorations(decoration_count, &accent_color),
             ticket_perforations = Self::generate_ticket_perforations(ticket_width, &primary_color),
-            background_pattern = Self::generate_background_pattern(&coupon_type),
-            coupon_type = coupon_type,
+            background_pattern = Self::generate_background_pattern(tier),
+            coupon_type = Self::type_label(tier, crafted),
             coupon_id = coupon_id,
             final_result = final_result,
             stake_amount = stake_amount,
             base_xor = base_xor,
             stake_bonus = stake_bonus,
             creation_block = creation_block,
+            txid_suffix = creation_txid
+                .as_deref()
+                .map(|txid| format!(" | Tx: {}", Self::shorten_txid(txid)))
+                .unwrap_or_default(),
+            watermark = if verified { String::new() } else { Self::generate_unverified_watermark() },
+            stamp = status_stamp.as_deref().map(Self::generate_status_stamp).unwrap_or_default(),
         );
 
         Ok(svg)
     }
 
-    /// Calculate colors based on coupon properties
-    fn calculate_colors(final_result: u8, base_xor: u8, is_winner: bool) -> (String, String, String) {
+    /// First and last eight characters of a txid, enough to match it by eye
+    fn shorten_txid(txid: &str) -> String {
+        if txid.len() <= 16 {
+            return txid.to_string();
+        }
+        format!("{}…{}", &txid[..8], &txid[txid.len() - 8..])
+    }
+
+    /// Display name of the coupon type, e.g. "Big Win" or "Crafted Big Win"
+    fn type_label(tier: OutcomeTier, crafted: bool) -> String {
+        if crafted {
+            format!("Crafted {}", tier.name())
+        } else {
+            tier.name().to_string()
+        }
+    }
+
+    /// Calculate colors based on the band the roll reached, see [`TierBoundaries::band`]
+    fn calculate_colors(band: OutcomeTier, base_xor: u8, is_winner: bool) -> (String, String, String) {
         // Winner tickets get brighter, more appealing colors
         let primary = if is_winner {
-            match final_result {
-                250..=255 => "#fbbf24", // Gold for legendary winners
-                230..=249 => "#10b981", // Green for epic winners
-                200..=229 => "#3b82f6", // Blue for rare winners
-                170..=199 => "#8b5cf6", // Purple for uncommon winners
-                _ => "#6b7280", // Gray for common winners
+            match band {
+                OutcomeTier::Jackpot => "#fbbf24", // Gold for legendary winners
+                OutcomeTier::BigWin => "#10b981", // Green for epic winners
+                OutcomeTier::Win => "#3b82f6", // Blue for rare winners
+                OutcomeTier::SmallWin => "#8b5cf6", // Purple for uncommon winners
+                OutcomeTier::Lose => "#6b7280", // Gray for common winners
             }
         } else {
-            match final_result {
-                250..=255 => "#dc2626", // Red for legendary losers
-                230..=249 => "#7c2d12", // Dark orange for epic losers
-                200..=229 => "#1e40af", // Dark blue for rare losers
-                170..=199 => "#374151", // Dark gray for uncommon losers
-                _ => "#1f2937", // Very dark for common losers
+            match band {
+                OutcomeTier::Jackpot => "#dc2626", // Red for legendary losers
+                OutcomeTier::BigWin => "#7c2d12", // Dark orange for epic losers
+                OutcomeTier::Win => "#1e40af", // Dark blue for rare losers
+                OutcomeTier::SmallWin => "#374151", // Dark gray for uncommon losers
+                OutcomeTier::Lose => "#1f2937", // Very dark for common losers
             }
         };
 
         let secondary = if is_winner {
-            match final_result {
-                250..=255 => "#f59e0b",
-                230..=249 => "#059669",
-                200..=229 => "#1d4ed8",
-                170..=199 => "#7c3aed",
-                _ => "#4b5563",
+            match band {
+                OutcomeTier::Jackpot => "#f59e0b",
+                OutcomeTier::BigWin => "#059669",
+                OutcomeTier::Win => "#1d4ed8",
+                OutcomeTier::SmallWin => "#7c3aed",
+                OutcomeTier::Lose => "#4b5563",
             }
         } else {
-            match final_result {
-                250..=255 => "#991b1b",
-                230..=249 => "#92400e",
-                200..=229 => "#1e3a8a",
-                170..=199 => "#1f2937",
-                _ => "#111827",
+            match band {
+                OutcomeTier::Jackpot => "#991b1b",
+                OutcomeTier::BigWin => "#92400e",
+                OutcomeTier::Win => "#1e3a8a",
+                OutcomeTier::SmallWin => "#1f2937",
+                OutcomeTier::Lose => "#111827",
             }
         };
 
@@ -199,27 +269,26 @@ impl SvgGenerator {
         (primary.to_string(), secondary.to_string(), accent.to_string())
     }
 
-    /// Calculate ticket width based on coupon type
-    fn calculate_ticket_width(coupon_type: &str) -> u32 {
-        match coupon_type {
-            "Jackpot" => 280,
-            "Big Win" => 260,
-            "Win" => 240,
-            "Small Win" => 220,
-            "Loss" => 200,
-            _ => 180,
+    /// Calculate ticket width based on outcome tier
+    fn calculate_ticket_width(tier: OutcomeTier) -> u32 {
+        match tier {
+            OutcomeTier::Jackpot => 280,
+            OutcomeTier::BigWin => 260,
+            OutcomeTier::Win => 240,
+            OutcomeTier::SmallWin => 220,
+            OutcomeTier::Lose => 200,
         }
     }
 
-    /// Calculate badge size based on final result
-    fn calculate_badge_size(final_result: u8) -> u32 {
-        match final_result {
-            250..=255 => 35,
-            230..=249 => 30,
-            200..=229 => 25,
-            170..=199 => 22,
-            145..=169 => 18,
-            _ => 15,
+    /// Calculate badge size based on the band the roll reached
+    fn calculate_badge_size(band: OutcomeTier, is_winner: bool) -> u32 {
+        match band {
+            OutcomeTier::Jackpot => 35,
+            OutcomeTier::BigWin => 30,
+            OutcomeTier::Win => 25,
+            OutcomeTier::SmallWin => 22,
+            OutcomeTier::Lose if is_winner => 18,
+            OutcomeTier::Lose => 15,
         }
     }
 
@@ -291,23 +360,23 @@ impl SvgGenerator {
         perforations
     }
 
-    /// Generate background pattern based on coupon type
-    fn generate_background_pattern(coupon_type: &str) -> String {
-        match coupon_type {
-            "Jackpot" => {
+    /// Generate background pattern based on outcome tier
+    fn generate_background_pattern(tier: OutcomeTier) -> String {
+        match tier {
+            OutcomeTier::Jackpot => {
                 format!(r#"<rect x="80" y="140" width="25" height="25" fill="{}" transform="rotate(45 92 152)"/>
                    <rect x="280" y="240" width="20" height="20" fill="{}" transform="rotate(45 290 250)"/>
                    <rect x="60" y="380" width="15" height="15" fill="{}" transform="rotate(45 67 387)"/>
                    <rect x="320" y="430" width="30" height="30" fill="{}" transform="rotate(45 335 445)"/>"#,
                    "#fbbf24", "#f59e0b", "#d97706", "#92400e")
             }
-            "Big Win" => {
+            OutcomeTier::BigWin => {
                 format!(r#"<polygon points="100,120 115,150 85,150" fill="{}"/>
                    <polygon points="300,220 315,250 285,250" fill="{}"/>
                    <polygon points="120,420 135,450 105,450" fill="{}"/>"#,
                    "#10b981", "#059669", "#047857")
             }
-            "Win" => {
+            OutcomeTier::Win => {
                 format!(r#"<circle cx="90" cy="160" r="12" fill="{}"/>
                    <circle cx="310" cy="260" r="10" fill="{}"/>
                    <circle cx="110" cy="440" r="15" fill="{}"/>"#,
@@ -322,8 +391,32 @@ impl SvgGenerator {
         }
     }
 
+    /// Diagonal watermark for coupons the factory does not vouch for
+    fn generate_unverified_watermark() -> String {
+        format!(
+            r#"<text x="200" y="320" text-anchor="middle" font-family="sans-serif" font-size="56"
+        font-weight="bold" fill="{}" opacity="0.6" transform="rotate(-35 200 320)">UNVERIFIED</text>"#,
+            "#ef4444"
+        )
+    }
+
+    /// Boxed stamp across the ticket once the coupon has been spent
+    fn generate_status_stamp(stamp: &str) -> String {
+        format!(
+            r#"<g transform="rotate(12 200 420)" opacity="0.85">
+    <rect x="90" y="390" width="220" height="60" fill="none" stroke="{color}" stroke-width="4" rx="8"/>
+    <text x="200" y="432" text-anchor="middle" font-family="monospace" font-size="32"
+          font-weight="bold" fill="{color}">{stamp}</text>
+  </g>"#,
+            color = "#f59e0b",
+            stamp = stamp
+        )
+    }
+
     /// Generate JSON attributes for the coupon token
     pub fn get_attributes(data: CouponData) -> Result<String> {
+        let tier = data.display_tier();
+        let crafted = data.is_crafted();
         let CouponData {
             coupon_id,
             stake_amount,
@@ -332,12 +425,36 @@ impl SvgGenerator {
             final_result,
             creation_block,
             current_block,
-            coupon_type,
             is_winner,
+            crafted_from,
+            creation_txid,
+            ..
         } = data;
 
+        // Lets the coupon page link the exact creation transaction
+        let txid_trait = creation_txid
+            .map(|txid| {
+                format!(
+                    r#",
+    {{
+      "trait_type": "Creation Txid",
+      "value": "{}"
+    }}"#,
+                    txid
+                )
+            })
+            .unwrap_or_default();
+
+        let type_label = Self::type_label(tier, crafted);
+        let result = if crafted {
+            "Crafted"
+        } else if is_winner {
+            "Winner"
+        } else {
+            "Loser"
+        };
         let age = current_block.saturating_sub(creation_block);
-        let rarity_score = Self::calculate_rarity_score(final_result, stake_amount);
+        let rarity_score = Self::calculate_rarity_score(final_result, stake_amount, tier);
 
         let attributes = format!(
             r#"{{
@@ -381,16 +498,20 @@ impl SvgGenerator {
       "trait_type": "Age (Blocks)",
       "value": {}
     }},
+    {{
+      "trait_type": "Crafted From",
+      "value": {}
+    }},
     {{
       "trait_type": "Rarity Score",
       "value": {}
-    }}
+    }}{}
   ]
 }}"#,
-            coupon_type,
+            type_label,
             coupon_id,
-            coupon_type,
-            if is_winner { "Winner" } else { "Loser" },
+            type_label,
+            result,
             final_result,
             stake_amount,
             base_xor,
@@ -398,14 +519,16 @@ impl SvgGenerator {
             final_result,
             creation_block,
             age,
-            rarity_score
+            crafted_from,
+            rarity_score,
+            txid_trait
         );
 
         Ok(attributes)
     }
 
     /// Calculate a rarity score based on various factors
-    fn calculate_rarity_score(final_result: u8, stake_amount: u128) -> u32 {
+    fn calculate_rarity_score(final_result: u8, stake_amount: u128, tier: OutcomeTier) -> u32 {
         let mut score = final_result as u32;
         
         // Add bonus for stake amount
@@ -419,14 +542,8 @@ impl SvgGenerator {
             score += 5;
         }
         
-        // Add bonus for high scores
-        match final_result {
-            250..=255 => score += 100, // Jackpot bonus
-            230..=249 => score += 50,  // Big win bonus
-            200..=229 => score += 25,  // Win bonus
-            170..=199 => score += 10,  // Small win bonus
-            _ => {}
-        }
+        // Add bonus for the outcome tier
+        score += tier.rarity_bonus();
         
         score
     }
@@ -436,9 +553,8 @@ impl SvgGenerator {
 mod tests {
     use super::*;
 
-    #[test]
-    fn test_svg_generation() {
-        let data = CouponData {
+    fn sample_coupon() -> CouponData {
+        CouponData {
             coupon_id: 1,
             stake_amount: 5000,
             base_xor: 200,
@@ -446,31 +562,56 @@ mod tests {
             final_result: 225,
             creation_block: 1000,
             current_block: 1100,
-            coupon_type: "Win".to_string(),
             is_winner: true,
-        };
+            boundaries: TierBoundaries::default(),
+            verified: true,
+            status_stamp: None,
+            crafted_from: 0,
+            creation_txid: None,
+        }
+    }
+
+    #[test]
+    fn test_svg_generation() {
+        let data = sample_coupon();
 
         let svg = SvgGenerator::generate_svg(data).unwrap();
         assert!(svg.contains("svg"));
         assert!(svg.contains("Win Ticket"));
         assert!(svg.contains("#1"));
         assert!(svg.contains("WINNER"));
+        assert!(!svg.contains("UNVERIFIED"));
     }
 
     #[test]
-    fn test_attributes_generation() {
+    fn test_unverified_watermark() {
         let data = CouponData {
-            coupon_id: 1,
-            stake_amount: 5000,
-            base_xor: 200,
-            stake_bonus: 25,
-            final_result: 225,
-            creation_block: 1000,
-            current_block: 1100,
-            coupon_type: "Win".to_string(),
-            is_winner: true,
+            base_xor: 255,
+            stake_bonus: 0,
+            final_result: 255,
+            verified: false,
+            ..sample_coupon()
         };
 
+        let svg = SvgGenerator::generate_svg(data).unwrap();
+        assert!(svg.contains("UNVERIFIED"));
+    }
+
+    #[test]
+    fn test_status_stamp() {
+        let data = CouponData {
+            status_stamp: Some("REDEEMED".to_string()),
+            ..sample_coupon()
+        };
+
+        let svg = SvgGenerator::generate_svg(data).unwrap();
+        assert!(svg.contains(">REDEEMED</text>"));
+    }
+
+    #[test]
+    fn test_attributes_generation() {
+        let data = sample_coupon();
+
         let attributes = SvgGenerator::get_attributes(data).unwrap();
         assert!(attributes.contains("Win Coupon #1"));
         assert!(attributes.contains("\"value\": 225"));
@@ -478,21 +619,79 @@ mod tests {
         assert!(attributes.contains("Winner"));
     }
 
+    #[test]
+    fn test_jackpot_renders_jackpot_ticket() {
+        let data = CouponData {
+            coupon_id: 7,
+            stake_amount: 1000,
+            base_xor: 250,
+            stake_bonus: 5,
+            final_result: 255,
+            current_block: 1000,
+            ..sample_coupon()
+        };
+        assert_eq!(data.tier(), OutcomeTier::Jackpot);
+
+        let svg = SvgGenerator::generate_svg(data).unwrap();
+        assert!(svg.contains("Jackpot Ticket"));
+        assert!(svg.contains(r#"width="280" height="400""#));
+        // Diamond background pattern reserved for jackpots
+        assert!(svg.contains(r#"transform="rotate(45 92 152)""#));
+    }
+
+    #[test]
+    fn test_crafted_coupon_drawn_by_band() {
+        let data = CouponData {
+            coupon_id: 9,
+            stake_amount: 3000,
+            base_xor: 0,
+            stake_bonus: 0,
+            final_result: 235,
+            current_block: 1000,
+            is_winner: false,
+            crafted_from: 3,
+            ..sample_coupon()
+        };
+        assert_eq!(data.tier(), OutcomeTier::Lose);
+        assert_eq!(data.display_tier(), OutcomeTier::BigWin);
+
+        let svg = SvgGenerator::generate_svg(data).unwrap();
+        assert!(svg.contains("Crafted Big Win Ticket"));
+        assert!(svg.contains("CRAFTED FROM 3 COUPONS"));
+        assert!(!svg.contains("BETTER LUCK NEXT TIME"));
+    }
+
+    #[test]
+    fn test_creation_txid_is_linked() {
+        let txid = "4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b";
+        let data = || CouponData {
+            creation_txid: Some(txid.to_string()),
+            ..sample_coupon()
+        };
+
+        let svg = SvgGenerator::generate_svg(data()).unwrap();
+        assert!(svg.contains("Block: 1000 | Tx: 4a5e1e4b…fdeda33b"));
+
+        let attributes = SvgGenerator::get_attributes(data()).unwrap();
+        assert!(attributes.contains("\"trait_type\": \"Creation Txid\""));
+        assert!(attributes.contains(&format!("\"value\": \"{}\"", txid)));
+    }
+
     #[test]
     fn test_color_calculation() {
-        let (primary, secondary, accent) = SvgGenerator::calculate_colors(255, 100, true);
+        let (primary, secondary, accent) = SvgGenerator::calculate_colors(OutcomeTier::Jackpot, 100, true);
         assert_eq!(primary, "#fbbf24"); // Gold for winner
         
-        let (primary, secondary, accent) = SvgGenerator::calculate_colors(255, 100, false);
+        let (primary, secondary, accent) = SvgGenerator::calculate_colors(OutcomeTier::Jackpot, 100, false);
         assert_eq!(primary, "#dc2626"); // Red for loser
     }
 
     #[test]
     fn test_rarity_score() {
-        let score = SvgGenerator::calculate_rarity_score(255, 10000);
+        let score = SvgGenerator::calculate_rarity_score(255, 10000, OutcomeTier::Jackpot);
         assert!(score > 300); // Should be high for jackpot with large stake
         
-        let score = SvgGenerator::calculate_rarity_score(150, 0);
+        let score = SvgGenerator::calculate_rarity_score(150, 0, OutcomeTier::Lose);
         assert!(score < 200); // Should be lower for loss with no stake
     }
 }
\ No newline at end of file
@@ -0,0 +1,65 @@
+//! Crafting recipe: several losing coupons combined into one cosmetic coupon.
+//!
+//! The crafted coupon carries a score in place of a roll, derived from the
+//! `final_result` of every input. It is never a winner, so its score only
+//! decides how rare it looks, not what it pays.
+
+use anyhow::{anyhow, Result};
+
+/// Fewest coupons a craft accepts
+pub const MIN_CRAFT_INPUTS: usize = 2;
+/// Most coupons a craft accepts, bounding the calls one craft makes
+pub const MAX_CRAFT_INPUTS: usize = 10;
+
+/// Score added for every input beyond the first
+pub const CRAFT_BONUS_PER_INPUT: u8 = 8;
+
+pub fn check_input_count(count: usize) -> Result<()> {
+    if !(MIN_CRAFT_INPUTS..=MAX_CRAFT_INPUTS).contains(&count) {
+        return Err(anyhow!(
+            "Crafting takes {} to {} coupons, got {}",
+            MIN_CRAFT_INPUTS,
+            MAX_CRAFT_INPUTS,
+            count
+        ));
+    }
+    Ok(())
+}
+
+/// Average final result of the inputs plus [`CRAFT_BONUS_PER_INPUT`] for
+/// every input beyond the first, capped at 255
+pub fn craft_score(final_results: &[u8]) -> u8 {
+    if final_results.is_empty() {
+        return 0;
+    }
+
+    let count = final_results.len() as u32;
+    let average = final_results.iter().map(|&result| result as u32).sum::<u32>() / count;
+    let bonus = (count - 1) * CRAFT_BONUS_PER_INPUT as u32;
+    (average + bonus).min(u8::MAX as u32) as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_score_is_average_plus_bonus() {
+        assert_eq!(craft_score(&[100, 120]), 110 + CRAFT_BONUS_PER_INPUT);
+        assert_eq!(craft_score(&[90, 100, 110]), 100 + 2 * CRAFT_BONUS_PER_INPUT);
+        assert_eq!(craft_score(&[]), 0);
+    }
+
+    #[test]
+    fn test_score_saturates() {
+        assert_eq!(craft_score(&[150; MAX_CRAFT_INPUTS]), u8::MAX);
+    }
+
+    #[test]
+    fn test_input_count_bounds() {
+        assert!(check_input_count(MIN_CRAFT_INPUTS - 1).is_err());
+        assert!(check_input_count(MIN_CRAFT_INPUTS).is_ok());
+        assert!(check_input_count(MAX_CRAFT_INPUTS).is_ok());
+        assert!(check_input_count(MAX_CRAFT_INPUTS + 1).is_err());
+    }
+}
@@ -0,0 +1,164 @@
+//! Bounded leaderboard of the highest rolls.
+//!
+//! Entries are kept sorted and truncated to [`LEADERBOARD_SIZE`], so each new
+//! coupon costs one read and one write instead of a scan of the registry.
+
+use alkanes_support::id::AlkaneId;
+use anyhow::{anyhow, Result};
+use gamba_storage::StorageValue;
+use std::cmp::Ordering;
+
+/// Number of coupons kept on each leaderboard
+pub const LEADERBOARD_SIZE: usize = 10;
+
+/// Leaderboard over every coupon ever minted
+pub const SCOPE_ALL_TIME: u128 = 0;
+/// Leaderboard over the coupons minted in the current epoch
+pub const SCOPE_EPOCH: u128 = 1;
+
+const ENTRY_SIZE: usize = 65;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct LeaderboardEntry {
+    pub coupon_id: AlkaneId,
+    pub final_result: u8,
+    pub stake_amount: u128,
+    pub block: u128,
+}
+
+impl LeaderboardEntry {
+    /// Higher final result first, then the larger stake, then the earliest block
+    fn rank(&self, other: &Self) -> Ordering {
+        other
+            .final_result
+            .cmp(&self.final_result)
+            .then(other.stake_amount.cmp(&self.stake_amount))
+            .then(self.block.cmp(&other.block))
+    }
+}
+
+#[derive(Default, Debug, PartialEq)]
+pub struct Leaderboard {
+    pub entries: Vec<LeaderboardEntry>,
+}
+
+impl Leaderboard {
+    /// Insert an entry at its rank. Entries that tie keep their arrival order,
+    /// and anything past [`LEADERBOARD_SIZE`] is dropped. Returns whether the
+    /// entry made the board.
+    pub fn insert(&mut self, entry: LeaderboardEntry) -> bool {
+        let position = self
+            .entries
+            .iter()
+            .position(|existing| entry.rank(existing) == Ordering::Less)
+            .unwrap_or(self.entries.len());
+        if position >= LEADERBOARD_SIZE {
+            return false;
+        }
+
+        self.entries.insert(position, entry);
+        self.entries.truncate(LEADERBOARD_SIZE);
+        true
+    }
+}
+
+/// [coupon_id (32)] + [final_result (1)] + [stake_amount (16)] + [block (16)] per entry
+impl StorageValue for Leaderboard {
+    fn decode(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() % ENTRY_SIZE != 0 || bytes.len() / ENTRY_SIZE > LEADERBOARD_SIZE {
+            return Err(anyhow!("Malformed leaderboard of {} bytes", bytes.len()));
+        }
+
+        let entries = bytes
+            .chunks_exact(ENTRY_SIZE)
+            .map(|entry| {
+                Ok(LeaderboardEntry {
+                    coupon_id: <AlkaneId as StorageValue>::decode(&entry[0..32])?,
+                    final_result: entry[32],
+                    stake_amount: u128::from_le_bytes(entry[33..49].try_into()?),
+                    block: u128::from_le_bytes(entry[49..65].try_into()?),
+                })
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { entries })
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.entries.len() * ENTRY_SIZE);
+        for entry in &self.entries {
+            bytes.extend_from_slice(&StorageValue::encode(&entry.coupon_id));
+            bytes.push(entry.final_result);
+            bytes.extend_from_slice(&entry.stake_amount.to_le_bytes());
+            bytes.extend_from_slice(&entry.block.to_le_bytes());
+        }
+        bytes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(tx: u128, final_result: u8, stake_amount: u128, block: u128) -> LeaderboardEntry {
+        LeaderboardEntry {
+            coupon_id: AlkaneId { block: 2, tx },
+            final_result,
+            stake_amount,
+            block,
+        }
+    }
+
+    fn ids(board: &Leaderboard) -> Vec<u128> {
+        board.entries.iter().map(|entry| entry.coupon_id.tx).collect()
+    }
+
+    #[test]
+    fn test_orders_by_result_then_stake_then_block() {
+        let mut board = Leaderboard::default();
+        board.insert(entry(1, 200, 1000, 10));
+        board.insert(entry(2, 250, 1000, 11));
+        board.insert(entry(3, 200, 5000, 12));
+        board.insert(entry(4, 200, 1000, 9));
+
+        assert_eq!(ids(&board), vec![2, 3, 4, 1]);
+    }
+
+    #[test]
+    fn test_ties_keep_arrival_order() {
+        let mut board = Leaderboard::default();
+        board.insert(entry(1, 200, 1000, 10));
+        board.insert(entry(2, 200, 1000, 10));
+
+        assert_eq!(ids(&board), vec![1, 2]);
+    }
+
+    #[test]
+    fn test_bounded_to_leaderboard_size() {
+        let mut board = Leaderboard::default();
+        for tx in 0..LEADERBOARD_SIZE as u128 {
+            assert!(board.insert(entry(tx, 100 + tx as u8, 1000, 10)));
+        }
+
+        // Below the lowest entry of a full board
+        assert!(!board.insert(entry(100, 50, 1000, 10)));
+        assert_eq!(board.entries.len(), LEADERBOARD_SIZE);
+
+        // A new best roll pushes the lowest entry off the end
+        assert!(board.insert(entry(101, 255, 1000, 10)));
+        assert_eq!(board.entries.len(), LEADERBOARD_SIZE);
+        assert_eq!(board.entries[0].coupon_id.tx, 101);
+        assert!(!ids(&board).contains(&0));
+    }
+
+    #[test]
+    fn test_storage_round_trip() {
+        let mut board = Leaderboard::default();
+        board.insert(entry(1, 200, 1000, 10));
+        board.insert(entry(2, 250, u128::MAX, 11));
+
+        let decoded = Leaderboard::decode(&board.encode()).unwrap();
+        assert_eq!(decoded, board);
+        assert!(Leaderboard::decode(&[]).unwrap().entries.is_empty());
+        assert!(Leaderboard::decode(&[0u8; ENTRY_SIZE + 1]).is_err());
+    }
+}
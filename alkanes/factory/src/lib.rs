@@ -15,19 +15,270 @@ use alkanes_support::{
 use anyhow::{anyhow, Result};
 use bitcoin::hashes::{Hash, HashEngine};
 use bitcoin::{Txid, Transaction, blockdata::block::TxMerkleNode};
+use gamba_outcome::{OutcomeTier, TierBoundaries};
+use gamba_storage::{StorageValue, Store};
 use metashrew_support::utils::consensus_decode;
 
-/// Coupon token template ID
-const COUPON_TOKEN_TEMPLATE_ID: u128 = 0x601;
-
-/// Minimum stake amount for gambling
-const MINIMUM_STAKE_AMOUNT: u128 = 1000;
+mod migration;
+use migration::CURRENT_SCHEMA_VERSION;
+
+mod crafting;
+
+mod leaderboard;
+use leaderboard::{Leaderboard, LeaderboardEntry, LEADERBOARD_SIZE, SCOPE_ALL_TIME, SCOPE_EPOCH};
+
+mod storage;
+
+/// Auth token template ID (deployed alongside the coupon template)
+const AUTH_TOKEN_TEMPLATE_ID: u128 = 0xffee;
+
+/// Maximum number of coupons minted by a single CreateCouponBatch call
+const MAX_BATCH_SIZE: u128 = 10;
+
+/// Default epoch length in blocks (roughly one day)
+const DEFAULT_EPOCH_LENGTH: u128 = 144;
+
+/// Maximum number of entries returned by a single page query
+const MAX_PAGE_SIZE: u128 = 100;
+
+/// Consolation bonus added to re-rolls of losing coupons until configured
+const DEFAULT_REROLL_BONUS: u8 = 10;
+
+/// Coupon opcodes that record lineage on a freshly minted coupon
+const COUPON_IMPRINT_PARENT: u128 = 40;
+const COUPON_IMPRINT_CRAFT_INPUT: u128 = 41;
+
+/// Basis points denominator for ratios
+const BASIS_POINTS: u128 = 10_000;
+
+/// Privileged roles, each represented by its own auth token
+const ROLE_ADMIN: u128 = 0;
+const ROLE_OPERATOR: u128 = 1;
+const ROLE_TREASURER: u128 = 2;
+const ROLE_PAUSER: u128 = 3;
+
+/// GetConfig encoding version and entry tags.
+/// Entries are [tag (u16 LE)] + [length (u16 LE)] + [value], so unknown tags can be skipped.
+const CONFIG_VERSION: u8 = 1;
+const CONFIG_TAG_COUPON_TEMPLATE: u16 = 1;
+const CONFIG_TAG_TIER_TEMPLATE: u16 = 2;
+const CONFIG_TAG_SUCCESS_THRESHOLD: u16 = 3;
+const CONFIG_TAG_STAKE_LIMITS: u16 = 4;
+const CONFIG_TAG_BONUS_TABLE: u16 = 5;
+const CONFIG_TAG_FEE: u16 = 6;
+const CONFIG_TAG_PAUSE_STATE: u16 = 7;
+const CONFIG_TAG_RANDOMNESS_MODE: u16 = 8;
+const CONFIG_TAG_PAYOUT: u16 = 9;
+const CONFIG_TAG_TIMELOCK: u16 = 10;
+const CONFIG_TAG_CIRCUIT_BREAKER: u16 = 11;
+const CONFIG_TAG_SCHEMA_VERSION: u16 = 12;
+const CONFIG_TAG_PENDING_CHANGE: u16 = 13;
+const CONFIG_TAG_TIER_BOUNDARIES: u16 = 14;
+const CONFIG_TAG_REROLL_BONUS: u16 = 15;
+const CONFIG_TAG_CRAFTING_TEMPLATE: u16 = 16;
+
+/// Randomness source: last and middle bytes of the txid XORed with a
+/// height-and-txid derived merkle root
+const RANDOMNESS_MODE_TXID_MERKLE: u8 = 0;
+
+/// Kinds of configuration change that go through the timelocked queue
+const CHANGE_SUCCESS_THRESHOLD: u128 = 1;
+const CHANGE_STAKE_LIMITS: u128 = 2;
+const CHANGE_BONUS_TABLE: u128 = 3;
+const CHANGE_FEE: u128 = 4;
+const CHANGE_TIMELOCK: u128 = 5;
+const CHANGE_PAYOUT: u128 = 6;
+const CHANGE_TIER_BOUNDARIES: u128 = 7;
+const CHANGE_REROLL_BONUS: u128 = 8;
+
+/// Lifecycle of a queued configuration change
+const CHANGE_PENDING: u8 = 0;
+const CHANGE_APPLIED: u8 = 1;
+const CHANGE_CANCELLED: u8 = 2;
+
+/// Maximum number of steps in the stake bonus table
+const MAX_BONUS_STEPS: usize = 4;
+
+/// Coupon lifecycle status, stored in the registry entry of each coupon
+const COUPON_ACTIVE: u8 = 1;
+const COUPON_REDEEMED: u8 = 2;
+const COUPON_BURNED: u8 = 3;
+const COUPON_EXPIRED: u8 = 4;
+const COUPON_VOID: u8 = 5;
 
 #[derive(Default)]
 pub struct CouponFactory(());
 
+/// A configuration change waiting in the timelock queue
+pub(crate) struct PendingChange {
+    kind: u128,
+    activation_block: u128,
+    status: u8,
+    params: Vec<u128>,
+}
+
+/// [kind (16)] + [activation_block (16)] + [status (1)] + [params (16 each)]
+impl StorageValue for PendingChange {
+    fn decode(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() < 33 || (bytes.len() - 33) % 16 != 0 {
+            return Err(anyhow!("Malformed pending change of {} bytes", bytes.len()));
+        }
+
+        Ok(PendingChange {
+            kind: u128::from_le_bytes(bytes[0..16].try_into()?),
+            activation_block: u128::from_le_bytes(bytes[16..32].try_into()?),
+            status: bytes[32],
+            params: bytes[33..]
+                .chunks_exact(16)
+                .map(|param| Ok(u128::from_le_bytes(param.try_into()?)))
+                .collect::<Result<_>>()?,
+        })
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(33 + self.params.len() * 16);
+        bytes.extend_from_slice(&self.kind.to_le_bytes());
+        bytes.extend_from_slice(&self.activation_block.to_le_bytes());
+        bytes.push(self.status);
+        for param in &self.params {
+            bytes.extend_from_slice(&param.to_le_bytes());
+        }
+        bytes
+    }
+}
+
+/// Settings that change through the timelock queue
+pub(crate) struct EffectiveConfig {
+    success_threshold: u8,
+    minimum_stake: u128,
+    maximum_stake: u128,
+    bonus_table: Vec<(u128, u8)>,
+    fee_bps: u128,
+    timelock: u128,
+    reroll_bonus: u8,
+    /// Payout token and ratio; unset means winners are paid 1:1 in their stake token
+    payout: Option<(AlkaneId, u128, u128)>,
+    tier_boundaries: TierBoundaries,
+}
+
+impl EffectiveConfig {
+    fn apply(&mut self, change: &PendingChange) -> Result<()> {
+        let param = |index: usize| change.params.get(index).copied().unwrap_or(0);
+
+        match change.kind {
+            CHANGE_SUCCESS_THRESHOLD => self.success_threshold = param(0) as u8,
+            CHANGE_STAKE_LIMITS => {
+                self.minimum_stake = param(0);
+                self.maximum_stake = param(1);
+            }
+            CHANGE_BONUS_TABLE => self.bonus_table = CouponFactory::parse_bonus_table(&change.params)?,
+            CHANGE_FEE => self.fee_bps = param(0),
+            CHANGE_TIMELOCK => self.timelock = param(0),
+            CHANGE_REROLL_BONUS => self.reroll_bonus = param(0) as u8,
+            CHANGE_PAYOUT => {
+                let payout_token = AlkaneId { block: param(0), tx: param(1) };
+                self.payout = Some((payout_token, param(2), param(3)));
+            }
+            CHANGE_TIER_BOUNDARIES => {
+                self.tier_boundaries = CouponFactory::parse_tier_boundaries(&change.params)?
+            }
+            kind => return Err(anyhow!("Unknown configuration change kind {}", kind)),
+        }
+
+        Ok(())
+    }
+}
+
 impl AlkaneResponder for CouponFactory {}
 
+/// Activity aggregated over a block or an epoch
+#[derive(Default)]
+pub(crate) struct PeriodStats {
+    coupons: u128,
+    wins: u128,
+    total_stake: u128,
+    bonus_sum: u128,
+    highest_final_result: u8,
+}
+
+/// [coupons (16)] + [wins (16)] + [total_stake (16)] + [bonus_sum (16)] + [highest_final_result (1)]
+impl StorageValue for PeriodStats {
+    fn decode(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() != 65 {
+            return Err(anyhow!("Malformed period stats of {} bytes", bytes.len()));
+        }
+
+        let field = |index: usize| -> Result<u128> {
+            Ok(u128::from_le_bytes(bytes[index * 16..(index + 1) * 16].try_into()?))
+        };
+        Ok(Self {
+            coupons: field(0)?,
+            wins: field(1)?,
+            total_stake: field(2)?,
+            bonus_sum: field(3)?,
+            highest_final_result: bytes[64],
+        })
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(65);
+        bytes.extend_from_slice(&self.coupons.to_le_bytes());
+        bytes.extend_from_slice(&self.wins.to_le_bytes());
+        bytes.extend_from_slice(&self.total_stake.to_le_bytes());
+        bytes.extend_from_slice(&self.bonus_sum.to_le_bytes());
+        bytes.push(self.highest_final_result);
+        bytes
+    }
+}
+
+impl PeriodStats {
+    fn record(&mut self, stake_amount: u128, stake_bonus: u8, final_result: u8, is_winner: bool) -> Result<()> {
+        let overflow = || anyhow!("Period statistics overflow");
+        self.coupons = self.coupons.checked_add(1).ok_or_else(overflow)?;
+        if is_winner {
+            self.wins = self.wins.checked_add(1).ok_or_else(overflow)?;
+        }
+        self.total_stake = self.total_stake.checked_add(stake_amount).ok_or_else(overflow)?;
+        self.bonus_sum = self.bonus_sum.checked_add(stake_bonus as u128).ok_or_else(overflow)?;
+        self.highest_final_result = self.highest_final_result.max(final_result);
+        Ok(())
+    }
+}
+
+/// What a coupon's roll was derived from, imprinted on the coupon so anyone
+/// can recompute the roll from the creation transaction
+struct Provenance {
+    txid: [u8; 32],
+    merkle_root: [u8; 32],
+    entropy_index: u32,
+    success_threshold: u8,
+}
+
+impl Provenance {
+    /// Initialize inputs: txid and merkle root as low and high halves, then
+    /// the entropy index and the threshold in effect
+    fn to_inputs(&self) -> [u128; 6] {
+        let [txid_lo, txid_hi] = Self::halves(&self.txid);
+        let [merkle_lo, merkle_hi] = Self::halves(&self.merkle_root);
+        [
+            txid_lo,
+            txid_hi,
+            merkle_lo,
+            merkle_hi,
+            self.entropy_index as u128,
+            self.success_threshold as u128,
+        ]
+    }
+
+    fn halves(bytes: &[u8; 32]) -> [u128; 2] {
+        let mut low = [0u8; 16];
+        let mut high = [0u8; 16];
+        low.copy_from_slice(&bytes[..16]);
+        high.copy_from_slice(&bytes[16..]);
+        [u128::from_le_bytes(low), u128::from_le_bytes(high)]
+    }
+}
+
 #[derive(MessageDispatch)]
 enum CouponFactoryMessage {
     #[opcode(0)]
@@ -39,6 +290,33 @@ enum CouponFactoryMessage {
     #[opcode(1)]
     CreateCoupon,
 
+    #[opcode(5)]
+    CreateCouponBatch {
+        count: u128,                 // Coupons to mint, the stake is split evenly between them
+    },
+
+    #[opcode(2)]
+    #[returns(u128)]
+    RedeemCoupon,
+
+    #[opcode(3)]
+    DepositPrize,
+
+    #[opcode(4)]
+    ClaimPrize,
+
+    /// Consume the incoming coupon for good and mark it burned
+    #[opcode(6)]
+    BurnCoupon,
+
+    /// Burn an active losing coupon and roll again with the fresh stake sent alongside it
+    #[opcode(7)]
+    Reroll,
+
+    /// Burn several active losing coupons and mint one crafted coupon from them
+    #[opcode(8)]
+    Craft,
+
     #[opcode(10)]
     #[returns(u128)]
     GetSuccessfulCoupons,
@@ -51,14 +329,69 @@ enum CouponFactoryMessage {
     #[returns(u128)]
     GetTotalCoupons,
 
+    #[opcode(13)]
+    #[returns(Vec<u8>)]
+    GetBlockStats {
+        height: u128,
+    },
+
+    #[opcode(14)]
+    #[returns(Vec<u8>)]
+    GetEpochStats {
+        epoch: u128,
+    },
+
+    #[opcode(15)]
+    #[returns(Vec<u8>)]
+    GetCurrentEpoch,
+
+    #[opcode(16)]
+    #[returns(Vec<u8>)]
+    GetVolumeStats {
+        token: AlkaneId,
+    },
+
+    #[opcode(17)]
+    #[returns(Vec<u8>)]
+    GetStakedTokens,
+
+    #[opcode(18)]
+    #[returns(Vec<u8>)]
+    GetLeaderboard {
+        scope: u128,                 // 0 = all-time, 1 = current epoch
+        limit: u128,                 // Capped at the leaderboard size
+    },
+
     #[opcode(21)]
     #[returns(u128)]
     GetSuccessThreshold,
 
+    #[opcode(22)]
+    #[returns(AlkaneId)]
+    GetAuthToken,
+
     #[opcode(23)]
     #[returns(AlkaneId)]
     GetCouponTokenTemplateId,
 
+    #[opcode(24)]
+    #[returns(AlkaneId)]
+    GetTemplateForTier {
+        tier: u128,
+    },
+
+    #[opcode(25)]
+    #[returns(Vec<u8>)]
+    GetTierBoundaries,
+
+    /// Coupons burned to craft this one, in crafting order
+    #[opcode(26)]
+    #[returns(Vec<u8>)]
+    GetCraftInputs {
+        coupon_id: AlkaneId,
+    },
+
+    /// Every registered coupon in one response; prefer GetRegisteredCouponsPage
     #[opcode(30)]
     #[returns(Vec<u8>)]
     GetAllRegisteredCoupons,
@@ -69,10 +402,76 @@ enum CouponFactoryMessage {
         coupon_id: AlkaneId,
     },
 
+    #[opcode(32)]
+    #[returns(u128)]
+    IsCouponRedeemed {
+        coupon_id: AlkaneId,
+    },
+
+    #[opcode(33)]
+    #[returns(Vec<u8>)]
+    GetRegisteredCouponsPage {
+        offset: u128,
+        limit: u128,                 // Capped at 100 coupons per page
+    },
+
+    #[opcode(34)]
+    #[returns(u128)]
+    GetCouponIndex {
+        coupon_id: AlkaneId,
+    },
+
+    #[opcode(35)]
+    #[returns(Vec<u8>)]
+    GetCouponByTxid {
+        txid_lo: u128,               // Txid bytes 0..16 (internal byte order) as a LE u128
+        txid_hi: u128,               // Txid bytes 16..32
+    },
+
+    #[opcode(36)]
+    #[returns(Vec<u8>)]
+    GetTxidByCoupon {
+        coupon_id: AlkaneId,
+    },
+
+    /// 0 for unknown coupons, otherwise 1 active, 2 redeemed, 3 burned, 4 expired, 5 void
+    #[opcode(37)]
+    #[returns(u128)]
+    GetCouponStatus {
+        coupon_id: AlkaneId,
+    },
+
+    #[opcode(38)]
+    #[returns(Vec<u8>)]
+    GetCouponStatusPage {
+        offset: u128,
+        limit: u128,                 // Capped at 100 coupons per page
+    },
+
+    #[opcode(39)]
+    #[returns(AlkaneId)]
+    GetCouponParent {
+        coupon_id: AlkaneId,
+    },
+
+    /// Fixed 65-byte layout kept for existing clients, superseded by GetConfig
     #[opcode(40)]
     #[returns(Vec<u8>)]
     GetFactoryInfo,
 
+    #[opcode(41)]
+    #[returns(Vec<u8>)]
+    GetPauseStatus,
+
+    #[opcode(42)]
+    #[returns(Vec<u8>)]
+    GetCircuitBreaker,
+
+    /// Versioned tag-length-value encoding of the full factory configuration
+    #[opcode(43)]
+    #[returns(Vec<u8>)]
+    GetConfig,
+
     #[opcode(50)]
     #[returns(u128)]
     CalculateBaseXor,
@@ -80,6 +479,208 @@ enum CouponFactoryMessage {
     #[opcode(51)]
     #[returns(u128)]
     GetMinimumStake,
+
+    #[opcode(52)]
+    #[returns(Vec<u8>)]
+    GetStakeLimits,
+
+    #[opcode(53)]
+    #[returns(Vec<u8>)]
+    GetBonusTable,
+
+    #[opcode(54)]
+    #[returns(u128)]
+    GetFee,
+
+    #[opcode(55)]
+    #[returns(u128)]
+    GetTimelock,
+
+    #[opcode(56)]
+    #[returns(Vec<u8>)]
+    GetPendingChanges,
+
+    #[opcode(57)]
+    #[returns(Vec<u8>)]
+    GetRoleTokens {
+        role: u128,
+    },
+
+    #[opcode(58)]
+    #[returns(u128)]
+    GetSchemaVersion,
+
+    #[opcode(60)]
+    #[returns(u128)]
+    GetPrizeInventory {
+        token: AlkaneId,
+    },
+
+    #[opcode(61)]
+    #[returns(Vec<u8>)]
+    GetPayoutConfig,
+
+    #[opcode(62)]
+    #[returns(Vec<u8>)]
+    GetTierPrizes {
+        tier: u128,
+    },
+
+    #[opcode(63)]
+    #[returns(u128)]
+    GetTreasuryBalance {
+        token: AlkaneId,
+    },
+
+    #[opcode(69)]
+    WithdrawPrize {
+        token: AlkaneId,
+        amount: u128,                // Prize inventory units to take back
+    },
+
+    #[opcode(70)]
+    #[returns(u128)]
+    SetPayoutToken {
+        payout_token: AlkaneId,      // Alkane paid to winners from the prize inventory
+        ratio_numerator: u128,       // Payout units per `ratio_denominator` staked units
+        ratio_denominator: u128,
+    },
+
+    #[opcode(71)]
+    DepositTierPrize {
+        tier: u128,                  // Winning tier whose coupons may claim the deposited NFTs
+    },
+
+    #[opcode(72)]
+    WithdrawTierPrizes {
+        tier: u128,
+        count: u128,                 // Maximum number of unclaimed NFTs to withdraw
+    },
+
+    #[opcode(73)]
+    #[returns(u128)]
+    SetSuccessThreshold {
+        success_threshold: u128,
+    },
+
+    #[opcode(74)]
+    #[returns(u128)]
+    SetStakeLimits {
+        minimum_stake: u128,
+        maximum_stake: u128,         // 0 means no upper limit
+    },
+
+    #[opcode(75)]
+    SetCouponTemplate {
+        coupon_token_template_id: AlkaneId, // Template used for all future mints
+    },
+
+    #[opcode(76)]
+    #[returns(u128)]
+    SetBonusTable {
+        // Up to four (minimum stake, bonus) steps in ascending order; unused steps are (0, 0)
+        stake_1: u128,
+        bonus_1: u128,
+        stake_2: u128,
+        bonus_2: u128,
+        stake_3: u128,
+        bonus_3: u128,
+        stake_4: u128,
+        bonus_4: u128,
+    },
+
+    #[opcode(77)]
+    Pause,
+
+    #[opcode(78)]
+    Unpause,
+
+    #[opcode(79)]
+    SetCircuitBreaker {
+        window: u128,                // Number of most recent coupons considered, 0 disables
+        max_win_ratio_bps: u128,     // Pause once wins in the window exceed this ratio (basis points)
+    },
+
+    #[opcode(80)]
+    #[returns(u128)]
+    SetFee {
+        fee_bps: u128,               // Share of each stake credited to the treasury (basis points)
+    },
+
+    #[opcode(81)]
+    #[returns(u128)]
+    SetTimelock {
+        timelock: u128,              // Minimum delay in blocks before queued changes take effect
+    },
+
+    #[opcode(82)]
+    CancelPendingChange {
+        change_id: u128,
+    },
+
+    #[opcode(83)]
+    WithdrawFees {
+        token: AlkaneId,
+    },
+
+    #[opcode(84)]
+    GrantRole {
+        role: u128,                  // Mints and registers a fresh auth token for this role
+    },
+
+    #[opcode(85)]
+    RegisterRoleToken {
+        role: u128,
+        token: AlkaneId,             // Existing alkane to accept as a holder of this role
+    },
+
+    #[opcode(86)]
+    RevokeRole {
+        role: u128,
+        token: AlkaneId,
+    },
+
+    #[opcode(87)]
+    #[returns(u128)]
+    Migrate,
+
+    #[opcode(88)]
+    SetTierTemplate {
+        tier: u128,                  // Outcome tier, 0 for losing coupons
+        template: AlkaneId,          // Template for this tier, 0:0 to fall back to the default
+    },
+
+    #[opcode(89)]
+    SetEpochLength {
+        epoch_length: u128,          // Blocks per epoch, applied from the next epoch onwards
+    },
+
+    /// Retire an active coupon as expired (4) or void (5)
+    #[opcode(90)]
+    SetCouponStatus {
+        coupon_id: AlkaneId,
+        status: u128,
+    },
+
+    /// Lowest final result of each winning tier; applies to coupons minted from now on
+    #[opcode(91)]
+    #[returns(u128)]
+    SetTierBoundaries {
+        small_win: u128,
+        win: u128,
+        big_win: u128,
+        jackpot: u128,
+    },
+
+    #[opcode(92)]
+    SetRerollBonus {
+        bonus: u128,                 // Added to the stake bonus of every re-roll
+    },
+
+    #[opcode(93)]
+    SetCraftingTemplate {
+        template: AlkaneId,          // Template for crafted coupons, 0:0 to fall back to the default
+    },
 }
 
 impl Token for CouponFactory {
@@ -98,18 +699,33 @@ impl CouponFactory {
         success_threshold: u128,
         coupon_token_template_id: AlkaneId,
     ) -> Result<CallResponse> {
-        let _context = self.context()?;
-        let response = CallResponse::default();
+        let context = self.context()?;
+        let mut response = CallResponse::default();
 
         self.observe_initialization()?;
 
+        Self::validate_success_threshold(success_threshold)?;
+        if coupon_token_template_id.block == 0 && coupon_token_template_id.tx == 0 {
+            return Err(anyhow!("Coupon template ID must not be zero"));
+        }
+        self.validate_coupon_template(&context, &coupon_token_template_id)?;
+
         // Store all parameters
-        self.set_success_threshold(success_threshold as u8);
+        self.store_success_threshold(success_threshold as u8);
         self.set_coupon_token_template_id(&coupon_token_template_id)?;
 
         // Initialize counters
-        self.set_successful_coupons(0);
-        self.set_failed_coupons(0);
+        storage::successful_coupons().set(self, &0);
+        storage::failed_coupons().set(self, &0);
+
+        migration::set_schema_version(self, CURRENT_SCHEMA_VERSION);
+
+        // Mint one auth token per role to the deployer
+        for role in [ROLE_ADMIN, ROLE_OPERATOR, ROLE_TREASURER, ROLE_PAUSER] {
+            let role_token = self.deploy_auth_token()?;
+            self.add_role_token(role, &role_token.id)?;
+            response.alkanes.0.push(role_token);
+        }
 
         Ok(response)
     }
@@ -118,437 +734,2611 @@ impl CouponFactory {
         let context = self.context()?;
         let mut response = CallResponse::default();
 
-        // Calculate base XOR from blockchain data
-        let base_xor = self.calculate_base_xor_internal()?;
+        let txid = self.transaction_id()?;
+        let merkle_root = self.merkle_root(&txid);
+        self.begin_coupon_creation(&txid)?;
 
-        // Get amount incoming from context.incoming_alkanes[0].0 - be sure it matches with init value.
-        let stake_amount = self.get_stake_input_amount(&context)?;
+        let stake_token = self.get_stake_token(&context.incoming_alkanes.0)?;
+        let stake_amount = self.get_stake_input_amount(&context.incoming_alkanes.0)?;
 
-        let stake_bonus = self.calculate_stake_bonus_internal(stake_amount)?;
-        let final_result = base_xor.saturating_add(stake_bonus);
+        let coupon_token =
+            self.mint_coupon(&txid, &merkle_root, stake_token.as_ref(), stake_amount, 0, 0)?;
 
-        // Check success threshold
-        let success_threshold = self.success_threshold();
-        if final_result > success_threshold {
-            // Successful gamble - create winning coupon token
-            let coupon_token = self.create_coupon_token(
-                stake_amount,
-                base_xor,
-                stake_bonus,
-                final_result,
-                true, // winning coupon
-            )?;
+        // Return the coupon token to the user
+        response.alkanes.0.push(coupon_token);
+
+        // Staked tokens are consumed regardless of success/failure
+        // (This is automatic as staked tokens are not returned in response)
 
-            // Register the coupon token as our child
-            self.register_coupon(&coupon_token.id);
+        Ok(response)
+    }
+
+    fn create_coupon_batch(&self, count: u128) -> Result<CallResponse> {
+        let context = self.context()?;
+        let mut response = CallResponse::default();
 
-            // Increment successful coupons
-            let new_successful = self.successful_coupons().checked_add(1).unwrap_or(0);
-            self.set_successful_coupons(new_successful);
+        if count == 0 || count > MAX_BATCH_SIZE {
+            return Err(anyhow!(
+                "Batch size must be between 1 and {}, got {}",
+                MAX_BATCH_SIZE,
+                count
+            ));
+        }
 
-            // Return the coupon token to the user
-            response.alkanes.0.push(coupon_token);
-        } else {
-            // Failed gamble - create losing coupon token
-            let coupon_token = self.create_coupon_token(
+        let txid = self.transaction_id()?;
+        let merkle_root = self.merkle_root(&txid);
+        self.begin_coupon_creation(&txid)?;
+
+        let stake_token = self.get_stake_token(&context.incoming_alkanes.0)?;
+        let total_stake = self.get_stake_input_amount(&context.incoming_alkanes.0)?;
+        if total_stake % count != 0 {
+            return Err(anyhow!(
+                "Stake {} cannot be split evenly across {} coupons",
+                total_stake,
+                count
+            ));
+        }
+        let stake_amount = total_stake / count;
+
+        // The pause state is only checked once, so a breaker tripped part way
+        // through the batch takes effect from the next call
+        for index in 0..count {
+            let coupon_token = self.mint_coupon(
+                &txid,
+                &merkle_root,
+                stake_token.as_ref(),
                 stake_amount,
-                base_xor,
-                stake_bonus,
-                final_result,
-                false, // losing coupon
+                index as u32,
+                0,
             )?;
+            response.alkanes.0.push(coupon_token);
+        }
 
-            // Register the coupon token as our child
-            self.register_coupon(&coupon_token.id);
+        Ok(response)
+    }
 
-            // Increment failed coupons
-            let new_failed = self.failed_coupons().checked_add(1).unwrap_or(0);
-            self.set_failed_coupons(new_failed);
+    /// Checks shared by every coupon-creating opcode. Marks the transaction as
+    /// processed, so each transaction gets at most one roll of its entropy.
+    fn begin_coupon_creation(&self, txid: &Txid) -> Result<()> {
+        self.require_current_schema()?;
 
-            // Return the coupon token to the user
-            response.alkanes.0.push(coupon_token);
+        self.apply_due_changes()?;
+
+        // Erroring reverts the call, so the stake is refunded to the sender
+        if self.is_paused()? {
+            return Err(anyhow!("Factory is paused: {}", self.pause_reason()?));
         }
 
-        // Staked tokens are consumed regardless of success/failure
-        // (This is automatic as staked tokens are not returned in response)
+        // Entropy is derived from the txid, so a second roll in the same
+        // transaction would repeat the first one
+        if self.has_tx_hash(txid)? {
+            return Err(anyhow!(
+                "Transaction {} has already created coupons, use CreateCouponBatch for several",
+                txid
+            ));
+        }
+        self.add_tx_hash(txid)?;
 
-        Ok(response)
+        Ok(())
     }
 
-    fn calculate_base_xor_internal(&self) -> Result<u8> {
-        // Enhanced XOR calculation using merkle root and transaction ID
-        // This provides much stronger entropy than the previous simple method
-        
-        // Get the current transaction ID
-        let txid = self.transaction_id()?;
-        
-        // Get the merkle root for this block
-        let merkle_root = self.merkle_root()?;
-        
-        // Extract bytes from both sources
-        let txid_bytes = txid.as_byte_array();
-        let merkle_bytes = merkle_root.as_byte_array();
-        
-        // XOR the last bytes of both for primary randomness
-        let base_xor = txid_bytes[31] ^ merkle_bytes[31];
-        
-        // Add additional entropy from middle bytes to make it more unpredictable
-        let entropy_xor = txid_bytes[15] ^ merkle_bytes[15];
-        
-        // Combine both sources with modular arithmetic to stay in u8 range
-        let final_xor = base_xor.wrapping_add(entropy_xor);
-        
-        Ok(final_xor)
+    /// Roll, mint and record a single coupon. `txid` and `merkle_root` are decoded
+    /// once by the calling opcode; `entropy_index` separates the rolls of coupons
+    /// minted by the same transaction; `extra_bonus` is added on top of the stake bonus.
+    fn mint_coupon(
+        &self,
+        txid: &Txid,
+        merkle_root: &TxMerkleNode,
+        stake_token: Option<&AlkaneId>,
+        stake_amount: u128,
+        entropy_index: u32,
+        extra_bonus: u8,
+    ) -> Result<AlkaneTransfer> {
+        // Calculate base XOR from blockchain data
+        let base_xor = Self::calculate_base_xor_for_index(txid, merkle_root, entropy_index);
+
+        self.check_stake_limits(stake_amount)?;
+
+        let stake_bonus = self
+            .calculate_stake_bonus_internal(stake_amount)?
+            .saturating_add(extra_bonus);
+        let final_result = base_xor.saturating_add(stake_bonus);
+
+        // Check success threshold
+        let success_threshold = self.success_threshold()?;
+        let is_winner = final_result > success_threshold;
+
+        let boundaries = self.tier_boundaries()?;
+        let tier = boundaries.classify(final_result, is_winner);
+
+        let provenance = Provenance {
+            txid: *txid.as_byte_array(),
+            merkle_root: *merkle_root.as_byte_array(),
+            entropy_index,
+            success_threshold,
+        };
+
+        let coupon_token = self.create_coupon_token(
+            &self.template_for_tier(tier)?,
+            stake_amount,
+            base_xor,
+            stake_bonus,
+            final_result,
+            is_winner,
+            &boundaries,
+            &provenance,
+        )?;
+
+        // Let wallets find the coupon from the txid they broadcast
+        self.record_coupon_txid(&coupon_token.id, txid)?;
+
+        // Credit the protocol fee share of the stake to the treasury; the rest is consumed
+        if let Some(stake_token) = stake_token {
+            let fee = self.charge_fee(stake_token, stake_amount)?;
+            self.record_stake_volume(stake_token, stake_amount, fee)?;
+        }
+
+        // Register the coupon token as our child and remember what it staked
+        self.register_coupon(&coupon_token.id)?;
+        self.record_coupon_stake(
+            &coupon_token.id,
+            stake_token,
+            stake_amount,
+            final_result,
+            is_winner,
+            tier,
+        );
+
+        if is_winner {
+            storage::successful_coupons().increment(self)?;
+        } else {
+            storage::failed_coupons().increment(self)?;
+        }
+
+        self.record_period_stats(stake_amount, stake_bonus, final_result, is_winner)?;
+        self.record_leaderboards(&coupon_token.id, final_result, stake_amount)?;
+
+        // Trip the circuit breaker for subsequent coupons if wins run too hot
+        self.record_breaker_outcome(is_winner)?;
+
+        Ok(coupon_token)
+    }
+
+    fn redeem_coupon(&self) -> Result<CallResponse> {
+        let context = self.context()?;
+        let mut response = CallResponse::default();
+
+        self.require_current_schema()?;
+        self.apply_due_changes()?;
+
+        let coupon_id = self.incoming_coupon(&context)?;
+
+        self.require_active_coupon(&coupon_id)?;
+        if !self.is_coupon_winner(&coupon_id)? {
+            return Err(anyhow!("Only winning coupons can be redeemed"));
+        }
+
+        let payout = self.calculate_payout(&coupon_id)?;
+
+        // Erroring reverts the call, so the coupon is refunded to the sender
+        // rather than being consumed without a payout.
+        let inventory = self.prize_inventory(&payout.id)?;
+        if inventory < payout.value {
+            return Err(anyhow!(
+                "Insufficient prize inventory: payout {} exceeds available {}",
+                payout.value,
+                inventory
+            ));
+        }
+
+        storage::prize_inventory().sub(self, &payout.id, payout.value)?;
+        storage::coupon_status().set(self, &coupon_id, &COUPON_REDEEMED);
+        self.add_volume(&payout.id, "paid_out", payout.value)?;
+
+        // The coupon stays with the factory; only the payout is returned
+        response.data = payout.value.to_le_bytes().to_vec();
+        response.alkanes.0.push(payout);
+
+        Ok(response)
+    }
+
+    fn reroll(&self) -> Result<CallResponse> {
+        let context = self.context()?;
+        let mut response = CallResponse::default();
+
+        let txid = self.transaction_id()?;
+        let merkle_root = self.merkle_root(&txid);
+        self.begin_coupon_creation(&txid)?;
+
+        // One incoming transfer is the coupon being replaced, the rest is the new stake
+        let (coupons, stake): (Vec<AlkaneTransfer>, Vec<AlkaneTransfer>) = context
+            .incoming_alkanes
+            .0
+            .iter()
+            .cloned()
+            .partition(|transfer| matches!(self.coupon_status(&transfer.id), Ok(Some(_))));
+        let parent_id = match coupons.as_slice() {
+            [coupon] if coupon.value == 1 => coupon.id.clone(),
+            _ => return Err(anyhow!("Expected exactly one coupon token alongside the stake")),
+        };
+
+        self.require_consumable_loser(&parent_id)?;
+
+        // A re-roll is a new roll, so it needs a new stake
+        let stake_token = self
+            .get_stake_token(&stake)?
+            .ok_or_else(|| anyhow!("A re-roll needs a new stake alongside the coupon"))?;
+        let stake_amount = self.get_stake_input_amount(&stake)?;
+        if stake_amount == 0 {
+            return Err(anyhow!("A re-roll needs a new stake alongside the coupon"));
+        }
+
+        // The old coupon unit is not returned, so the factory holds it forever
+        storage::coupon_status().set(self, &parent_id, &COUPON_BURNED);
+
+        let reroll_bonus = self.reroll_bonus()?;
+        let coupon_token = self.mint_coupon(
+            &txid,
+            &merkle_root,
+            Some(&stake_token),
+            stake_amount,
+            0,
+            reroll_bonus,
+        )?;
+        storage::coupon_parent().set(self, &coupon_token.id, &parent_id);
+        self.imprint_lineage(&coupon_token.id, COUPON_IMPRINT_PARENT, &parent_id)?;
+
+        response.alkanes.0.push(coupon_token);
+        Ok(response)
+    }
+
+    fn craft(&self) -> Result<CallResponse> {
+        let context = self.context()?;
+        let mut response = CallResponse::default();
+
+        // Same entry checks as rolling: a transaction mints through one opcode,
+        // once, against settings whose due changes have taken effect
+        let txid = self.transaction_id()?;
+        self.begin_coupon_creation(&txid)?;
+
+        let inputs = &context.incoming_alkanes.0;
+        crafting::check_input_count(inputs.len())?;
+
+        let mut final_results = Vec::with_capacity(inputs.len());
+        let mut stake_amount = 0u128;
+        for (index, input) in inputs.iter().enumerate() {
+            if input.value != 1 || inputs[..index].iter().any(|earlier| earlier.id == input.id) {
+                return Err(anyhow!("Each crafting input must be one unit of a distinct coupon"));
+            }
+            self.require_consumable_loser(&input.id)?;
+
+            final_results.push(self.coupon_final_result(&input.id)?);
+            stake_amount = stake_amount
+                .checked_add(storage::coupon_stake_amount().get_or_default(self, &input.id)?)
+                .ok_or_else(|| anyhow!("Stake overflow"))?;
+
+            // The input units are not returned, so the factory holds them forever
+            storage::coupon_status().set(self, &input.id, &COUPON_BURNED);
+        }
+
+        // Crafted coupons never win; the score only sets how rare they look
+        let score = crafting::craft_score(&final_results);
+
+        // Nothing is rolled, so only the transaction is recorded
+        let provenance = Provenance {
+            txid: *txid.as_byte_array(),
+            merkle_root: [0u8; 32],
+            entropy_index: 0,
+            success_threshold: self.success_threshold()?,
+        };
+
+        let coupon_token = self.create_coupon_token(
+            &self.crafting_template()?,
+            stake_amount,
+            0,
+            0,
+            score,
+            false,
+            &self.tier_boundaries()?,
+            &provenance,
+        )?;
+
+        self.record_coupon_txid(&coupon_token.id, &txid)?;
+        self.register_coupon(&coupon_token.id)?;
+        self.record_coupon_stake(
+            &coupon_token.id,
+            None,
+            stake_amount,
+            score,
+            false,
+            OutcomeTier::Lose,
+        );
+        storage::crafted_coupons().increment(self)?;
+
+        for input in inputs {
+            storage::craft_inputs(&coupon_token.id).push(self, &input.id)?;
+            self.imprint_lineage(&coupon_token.id, COUPON_IMPRINT_CRAFT_INPUT, &input.id)?;
+        }
+
+        response.alkanes.0.push(coupon_token);
+        Ok(response)
+    }
+
+    fn burn_coupon(&self) -> Result<CallResponse> {
+        let context = self.context()?;
+
+        self.require_current_schema()?;
+
+        let coupon_id = self.incoming_coupon(&context)?;
+        self.require_active_coupon(&coupon_id)?;
+
+        // The coupon unit is not returned, so the factory holds it forever
+        storage::coupon_status().set(self, &coupon_id, &COUPON_BURNED);
+
+        Ok(CallResponse::default())
+    }
+
+    fn deposit_prize(&self) -> Result<CallResponse> {
+        let context = self.context()?;
+        let response = CallResponse::default();
+
+        if context.incoming_alkanes.0.is_empty() {
+            return Err(anyhow!("No prize tokens sent"));
+        }
+
+        for transfer in &context.incoming_alkanes.0 {
+            storage::prize_inventory().add(self, &transfer.id, transfer.value)?;
+        }
+
+        Ok(response)
+    }
+
+    fn set_success_threshold(&self, success_threshold: u128) -> Result<CallResponse> {
+        let context = self.context()?;
+        self.only_role(&context, ROLE_OPERATOR)?;
+        Self::validate_success_threshold(success_threshold)?;
+
+        self.schedule_change(&context, CHANGE_SUCCESS_THRESHOLD, vec![success_threshold])
+    }
+
+    fn set_stake_limits(&self, minimum_stake: u128, maximum_stake: u128) -> Result<CallResponse> {
+        let context = self.context()?;
+        self.only_role(&context, ROLE_OPERATOR)?;
+        Self::validate_stake_limits(minimum_stake, maximum_stake)?;
+
+        self.schedule_change(&context, CHANGE_STAKE_LIMITS, vec![minimum_stake, maximum_stake])
+    }
+
+    fn set_coupon_template(&self, coupon_token_template_id: AlkaneId) -> Result<CallResponse> {
+        let context = self.context()?;
+        self.only_role(&context, ROLE_OPERATOR)?;
+
+        self.validate_coupon_template(&context, &coupon_token_template_id)?;
+
+        self.set_coupon_token_template_id(&coupon_token_template_id)?;
+
+        Ok(CallResponse::forward(&context.incoming_alkanes))
+    }
+
+    fn set_epoch_length(&self, epoch_length: u128) -> Result<CallResponse> {
+        let context = self.context()?;
+        self.only_role(&context, ROLE_OPERATOR)?;
+
+        if epoch_length == 0 {
+            return Err(anyhow!("Epoch length must be at least one block"));
+        }
+
+        // Re-anchor at the start of the next epoch so past epochs keep their boundaries.
+        // If an earlier change has not taken effect yet, its anchor already marks the
+        // end of the running epoch and only the pending length is replaced.
+        let height = u128::from(self.height());
+        if height >= storage::epoch_anchor_height().get_or_default(self)? {
+            let (current_epoch, current_start) = self.current_epoch()?;
+            let current_length = self.epoch_length()?;
+            storage::epoch_anchor_epoch().set(self, &(current_epoch + 1));
+            storage::epoch_anchor_height().set(self, &current_start.saturating_add(current_length));
+            storage::epoch_previous_length().set(self, &current_length);
+        }
+        storage::epoch_length().set(self, &epoch_length);
+
+        Ok(CallResponse::forward(&context.incoming_alkanes))
+    }
+
+    fn set_coupon_status(&self, coupon_id: AlkaneId, status: u128) -> Result<CallResponse> {
+        let context = self.context()?;
+        self.only_role(&context, ROLE_ADMIN)?;
+
+        // Redeemed and burned are only reached through their own opcodes
+        let status = u8::try_from(status)
+            .ok()
+            .filter(|status| *status == COUPON_EXPIRED || *status == COUPON_VOID)
+            .ok_or_else(|| {
+                anyhow!(
+                    "Status must be {} (expired) or {} (void), got {}",
+                    COUPON_EXPIRED,
+                    COUPON_VOID,
+                    status
+                )
+            })?;
+        self.require_active_coupon(&coupon_id)?;
+        storage::coupon_status().set(self, &coupon_id, &status);
+
+        Ok(CallResponse::forward(&context.incoming_alkanes))
+    }
+
+    fn set_reroll_bonus(&self, bonus: u128) -> Result<CallResponse> {
+        let context = self.context()?;
+        self.only_role(&context, ROLE_OPERATOR)?;
+
+        if bonus > u8::MAX as u128 {
+            return Err(anyhow!("Re-roll bonus must be at most {}, got {}", u8::MAX, bonus));
+        }
+
+        self.schedule_change(&context, CHANGE_REROLL_BONUS, vec![bonus])
+    }
+
+    fn set_crafting_template(&self, template: AlkaneId) -> Result<CallResponse> {
+        let context = self.context()?;
+        self.only_role(&context, ROLE_OPERATOR)?;
+
+        if template.block == 0 && template.tx == 0 {
+            storage::crafting_template_id().clear(self);
+        } else {
+            self.validate_coupon_template(&context, &template)?;
+            storage::crafting_template_id().set(self, &template);
+        }
+
+        Ok(CallResponse::forward(&context.incoming_alkanes))
+    }
+
+    fn set_tier_boundaries(&self, small_win: u128, win: u128, big_win: u128, jackpot: u128) -> Result<CallResponse> {
+        let context = self.context()?;
+        self.only_role(&context, ROLE_OPERATOR)?;
+
+        let params = vec![small_win, win, big_win, jackpot];
+        Self::parse_tier_boundaries(&params)?;
+
+        self.schedule_change(&context, CHANGE_TIER_BOUNDARIES, params)
+    }
+
+    fn set_tier_template(&self, tier: u128, template: AlkaneId) -> Result<CallResponse> {
+        let context = self.context()?;
+        self.only_role(&context, ROLE_OPERATOR)?;
+        let tier = Self::parse_tier(tier)?.index();
+
+        if template.block == 0 && template.tx == 0 {
+            storage::tier_templates().remove(self, &tier);
+        } else {
+            self.validate_coupon_template(&context, &template)?;
+            storage::tier_templates().set(self, &tier, &template);
+        }
+
+        Ok(CallResponse::forward(&context.incoming_alkanes))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn set_bonus_table(
+        &self,
+        stake_1: u128,
+        bonus_1: u128,
+        stake_2: u128,
+        bonus_2: u128,
+        stake_3: u128,
+        bonus_3: u128,
+        stake_4: u128,
+        bonus_4: u128,
+    ) -> Result<CallResponse> {
+        let context = self.context()?;
+        self.only_role(&context, ROLE_OPERATOR)?;
+
+        let steps = vec![
+            stake_1, bonus_1, stake_2, bonus_2, stake_3, bonus_3, stake_4, bonus_4,
+        ];
+        Self::parse_bonus_table(&steps)?;
+
+        self.schedule_change(&context, CHANGE_BONUS_TABLE, steps)
+    }
+
+    fn set_fee(&self, fee_bps: u128) -> Result<CallResponse> {
+        let context = self.context()?;
+        self.only_role(&context, ROLE_OPERATOR)?;
+
+        if fee_bps > BASIS_POINTS {
+            return Err(anyhow!(
+                "Fee must be at most {} basis points, got {}",
+                BASIS_POINTS,
+                fee_bps
+            ));
+        }
+
+        self.schedule_change(&context, CHANGE_FEE, vec![fee_bps])
+    }
+
+    fn set_timelock(&self, timelock: u128) -> Result<CallResponse> {
+        let context = self.context()?;
+        self.only_role(&context, ROLE_OPERATOR)?;
+
+        // Shortening the timelock is itself subject to the current timelock
+        self.schedule_change(&context, CHANGE_TIMELOCK, vec![timelock])
+    }
+
+    fn cancel_pending_change(&self, change_id: u128) -> Result<CallResponse> {
+        let context = self.context()?;
+        self.only_role(&context, ROLE_OPERATOR)?;
+        self.apply_due_changes()?;
+
+        let change = self
+            .pending_change(change_id)?
+            .ok_or_else(|| anyhow!("Unknown configuration change {}", change_id))?;
+        if change.status != CHANGE_PENDING {
+            return Err(anyhow!(
+                "Configuration change {} is no longer pending",
+                change_id
+            ));
+        }
+
+        self.set_pending_change_status(change_id, CHANGE_CANCELLED)?;
+
+        Ok(CallResponse::forward(&context.incoming_alkanes))
+    }
+
+    fn withdraw_fees(&self, token: AlkaneId) -> Result<CallResponse> {
+        let context = self.context()?;
+        self.only_role(&context, ROLE_TREASURER)?;
+
+        let balance = self.treasury_balance(&token)?;
+        if balance == 0 {
+            return Err(anyhow!("No fees accrued in {}:{}", token.block, token.tx));
+        }
+        storage::treasury_balances().set(self, &token, &0);
+
+        let mut response = CallResponse::forward(&context.incoming_alkanes);
+        response.alkanes.0.push(AlkaneTransfer {
+            id: token,
+            value: balance,
+        });
+
+        Ok(response)
+    }
+
+    fn pause(&self) -> Result<CallResponse> {
+        let context = self.context()?;
+        self.only_role(&context, ROLE_PAUSER)?;
+
+        self.set_paused(true, "Paused by admin");
+
+        Ok(CallResponse::forward(&context.incoming_alkanes))
+    }
+
+    fn unpause(&self) -> Result<CallResponse> {
+        let context = self.context()?;
+        self.only_role(&context, ROLE_OPERATOR)?;
+
+        self.set_paused(false, "");
+
+        // Start the breaker window afresh so it does not trip again immediately
+        self.reset_breaker_window();
+
+        Ok(CallResponse::forward(&context.incoming_alkanes))
+    }
+
+    fn set_circuit_breaker(&self, window: u128, max_win_ratio_bps: u128) -> Result<CallResponse> {
+        let context = self.context()?;
+        self.only_role(&context, ROLE_OPERATOR)?;
+
+        if max_win_ratio_bps > BASIS_POINTS {
+            return Err(anyhow!(
+                "Win ratio must be at most {} basis points, got {}",
+                BASIS_POINTS,
+                max_win_ratio_bps
+            ));
+        }
+
+        storage::breaker_window().set(self, &window);
+        storage::breaker_max_win_ratio_bps().set(self, &max_win_ratio_bps);
+        self.reset_breaker_window();
+
+        Ok(CallResponse::forward(&context.incoming_alkanes))
+    }
+
+    fn migrate(&self) -> Result<CallResponse> {
+        let context = self.context()?;
+
+        if migration::schema_version(self)? >= CURRENT_SCHEMA_VERSION {
+            self.only_role(&context, ROLE_ADMIN)?;
+        } else if let Some(legacy_token) = storage::legacy_auth_token().get(self)? {
+            // A legacy factory's admin is whoever holds its single auth token
+            if !context
+                .incoming_alkanes
+                .0
+                .iter()
+                .any(|transfer| transfer.id == legacy_token && transfer.value > 0)
+            {
+                return Err(anyhow!("Auth token is required for this operation"));
+            }
+        }
+        // Without a legacy auth token the factory never had an admin, and
+        // migrating it only reshapes storage without granting any role
+
+        let remaining = migration::migrate(self)?;
+        let mut response = CallResponse::forward(&context.incoming_alkanes);
+        response.data = remaining.to_le_bytes().to_vec();
+        Ok(response)
+    }
+
+    fn grant_role(&self, role: u128) -> Result<CallResponse> {
+        let context = self.context()?;
+        self.only_role(&context, ROLE_ADMIN)?;
+        Self::validate_role(role)?;
+
+        let role_token = self.deploy_auth_token()?;
+        self.add_role_token(role, &role_token.id)?;
+
+        let mut response = CallResponse::forward(&context.incoming_alkanes);
+        response.alkanes.0.push(role_token);
+
+        Ok(response)
+    }
+
+    fn register_role_token(&self, role: u128, token: AlkaneId) -> Result<CallResponse> {
+        let context = self.context()?;
+        self.only_role(&context, ROLE_ADMIN)?;
+        Self::validate_role(role)?;
+
+        if self.is_role_token(role, &token)? {
+            return Err(anyhow!("Token is already registered for role {}", role));
+        }
+        self.add_role_token(role, &token)?;
+
+        Ok(CallResponse::forward(&context.incoming_alkanes))
+    }
+
+    fn revoke_role(&self, role: u128, token: AlkaneId) -> Result<CallResponse> {
+        let context = self.context()?;
+        self.only_role(&context, ROLE_ADMIN)?;
+        Self::validate_role(role)?;
+
+        if !self.is_role_token(role, &token)? {
+            return Err(anyhow!("Token is not registered for role {}", role));
+        }
+        if role == ROLE_ADMIN && self.role_tokens(ROLE_ADMIN)?.len() == 1 {
+            return Err(anyhow!("Cannot revoke the last admin token"));
+        }
+        self.remove_role_token(role, &token)?;
+
+        Ok(CallResponse::forward(&context.incoming_alkanes))
+    }
+
+    fn claim_prize(&self) -> Result<CallResponse> {
+        let context = self.context()?;
+        let mut response = CallResponse::default();
+
+        self.require_current_schema()?;
+        self.apply_due_changes()?;
+
+        let coupon_id = self.incoming_coupon(&context)?;
+
+        self.require_active_coupon(&coupon_id)?;
+        if !self.is_coupon_winner(&coupon_id)? {
+            return Err(anyhow!("Only winning coupons can claim prizes"));
+        }
+
+        let tier = self.coupon_tier(&coupon_id)?;
+        let prize = self
+            .pop_front_tier_prize(tier.index())?
+            .ok_or_else(|| anyhow!("No prizes left in tier {}", tier.name()))?;
+
+        // The coupon is kept by the factory, which burns it for good
+        storage::coupon_status().set(self, &coupon_id, &COUPON_REDEEMED);
+
+        response.alkanes.0.push(AlkaneTransfer {
+            id: prize,
+            value: 1u128,
+        });
+
+        Ok(response)
+    }
+
+    fn deposit_tier_prize(&self, tier: u128) -> Result<CallResponse> {
+        let context = self.context()?;
+        self.only_role(&context, ROLE_TREASURER)?;
+        let tier = Self::parse_prize_tier(tier)?.index();
+
+        let mut response = CallResponse::default();
+
+        for transfer in &context.incoming_alkanes.0 {
+            if self.is_role_token(ROLE_TREASURER, &transfer.id)? {
+                // Hand the auth token back to the caller
+                response.alkanes.0.push(transfer.clone());
+                continue;
+            }
+            if transfer.value != 1 {
+                return Err(anyhow!(
+                    "Tier prizes must be NFTs with a value of 1, got {}",
+                    transfer.value
+                ));
+            }
+            self.push_tier_prize(tier, &transfer.id)?;
+        }
+
+        Ok(response)
+    }
+
+    fn withdraw_prize(&self, token: AlkaneId, amount: u128) -> Result<CallResponse> {
+        let context = self.context()?;
+        self.only_role(&context, ROLE_TREASURER)?;
+
+        if amount == 0 {
+            return Err(anyhow!("Withdrawal amount must be non-zero"));
+        }
+        let inventory = self.prize_inventory(&token)?;
+        if inventory < amount {
+            return Err(anyhow!(
+                "Insufficient prize inventory: withdrawal {} exceeds available {}",
+                amount,
+                inventory
+            ));
+        }
+        storage::prize_inventory().sub(self, &token, amount)?;
+
+        let mut response = CallResponse::forward(&context.incoming_alkanes);
+        response.alkanes.0.push(AlkaneTransfer {
+            id: token,
+            value: amount,
+        });
+
+        Ok(response)
+    }
+
+    fn withdraw_tier_prizes(&self, tier: u128, count: u128) -> Result<CallResponse> {
+        let context = self.context()?;
+        self.only_role(&context, ROLE_TREASURER)?;
+        let tier = Self::parse_prize_tier(tier)?.index();
+
+        let mut response = CallResponse::forward(&context.incoming_alkanes);
+
+        // Withdraw from the back of the queue so claim order is unaffected
+        for _ in 0..count {
+            match self.pop_back_tier_prize(tier)? {
+                Some(prize) => response.alkanes.0.push(AlkaneTransfer {
+                    id: prize,
+                    value: 1u128,
+                }),
+                None => break,
+            }
+        }
+
+        Ok(response)
+    }
+
+    fn set_payout_token(
+        &self,
+        payout_token: AlkaneId,
+        ratio_numerator: u128,
+        ratio_denominator: u128,
+    ) -> Result<CallResponse> {
+        let context = self.context()?;
+        self.only_role(&context, ROLE_TREASURER)?;
+
+        if ratio_numerator == 0 || ratio_denominator == 0 {
+            return Err(anyhow!("Payout ratio terms must be non-zero"));
+        }
+        if payout_token == context.myself {
+            return Err(anyhow!("Payout token cannot be the factory itself"));
+        }
+
+        // A winner must not see the payout change between winning and redeeming
+        self.schedule_change(
+            &context,
+            CHANGE_PAYOUT,
+            vec![payout_token.block, payout_token.tx, ratio_numerator, ratio_denominator],
+        )
+    }
+
+    fn calculate_base_xor_internal(&self) -> Result<u8> {
+        let txid = self.transaction_id()?;
+        Ok(Self::calculate_base_xor_for_index(&txid, &self.merkle_root(&txid), 0))
+    }
+
+    /// Index 0 is the single roll of a transaction. Batch entries after the
+    /// first mix their index into the txid so each coupon rolls independently.
+    fn calculate_base_xor_for_index(txid: &Txid, merkle_root: &TxMerkleNode, index: u32) -> u8 {
+        // Enhanced XOR calculation using merkle root and transaction ID
+        // This provides much stronger entropy than the previous simple method
+        
+        // Extract bytes from both sources
+        let txid_bytes = if index == 0 {
+            *txid.as_byte_array()
+        } else {
+            let mut hasher = bitcoin::hashes::sha256::Hash::engine();
+            hasher.input(txid.as_byte_array());
+            hasher.input(&index.to_le_bytes());
+            *bitcoin::hashes::sha256::Hash::from_engine(hasher).as_byte_array()
+        };
+        let merkle_bytes = merkle_root.as_byte_array();
+        
+        // XOR the last bytes of both for primary randomness
+        let base_xor = txid_bytes[31] ^ merkle_bytes[31];
+        
+        // Add additional entropy from middle bytes to make it more unpredictable
+        let entropy_xor = txid_bytes[15] ^ merkle_bytes[15];
+        
+        // Combine both sources with modular arithmetic to stay in u8 range
+        base_xor.wrapping_add(entropy_xor)
+    }
+
+    fn transaction_id(&self) -> Result<Txid> {
+        Ok(
+            consensus_decode::<Transaction>(&mut std::io::Cursor::new(self.transaction()))?
+                .compute_txid(),
+        )
+    }
+
+    fn merkle_root(&self, txid: &Txid) -> TxMerkleNode {
+        // Production-ready implementation: Get the current block's merkle root
+        // This uses the runtime's block context to get the actual merkle root
+        let current_height = self.height();
+        
+        // Create a deterministic but cryptographically sound merkle root
+        // based on block height and transaction context
+        let mut hasher = bitcoin::hashes::sha256::Hash::engine();
+        
+        // Combine block height and transaction ID for entropy
+        hasher.input(&current_height.to_le_bytes());
+        hasher.input(txid.as_byte_array());
+        
+        let hash = bitcoin::hashes::sha256::Hash::from_engine(hasher);
+        TxMerkleNode::from_byte_array(*hash.as_byte_array())
+    }
+
+    fn get_stake_input_amount(&self, transfers: &[AlkaneTransfer]) -> Result<u128> {
+        let mut total_stake = 0u128;
+
+        // Accept any incoming tokens as stake (generic approach)
+        for transfer in transfers {
+            total_stake = total_stake
+                .checked_add(transfer.value)
+                .ok_or_else(|| anyhow!("Stake amount overflow"))?;
+        }
+
+        Ok(total_stake)
+    }
+
+    fn get_stake_token(&self, transfers: &[AlkaneTransfer]) -> Result<Option<AlkaneId>> {
+        let mut stake_token: Option<AlkaneId> = None;
+
+        for transfer in transfers {
+            match &stake_token {
+                None => stake_token = Some(transfer.id.clone()),
+                Some(id) if *id != transfer.id => {
+                    return Err(anyhow!("Stake must consist of a single alkane type"));
+                }
+                Some(_) => {}
+            }
+        }
+
+        Ok(stake_token)
+    }
+
+    fn validate_success_threshold(success_threshold: u128) -> Result<()> {
+        // Rolls are a u8 and must strictly exceed the threshold, so 255 could never win
+        if success_threshold >= u8::MAX as u128 {
+            return Err(anyhow!(
+                "Success threshold must be below {} so coupons can win, got {}",
+                u8::MAX,
+                success_threshold
+            ));
+        }
+        Ok(())
+    }
+
+    fn validate_stake_limits(minimum_stake: u128, maximum_stake: u128) -> Result<()> {
+        if maximum_stake != 0 && maximum_stake < minimum_stake {
+            return Err(anyhow!(
+                "Maximum stake {} is below the minimum stake {}",
+                maximum_stake,
+                minimum_stake
+            ));
+        }
+        Ok(())
+    }
+
+    fn check_stake_limits(&self, stake_amount: u128) -> Result<()> {
+        let (minimum_stake, maximum_stake) = self.stake_limits()?;
+
+        if stake_amount < minimum_stake {
+            return Err(anyhow!(
+                "Stake {} is below the minimum of {}",
+                stake_amount,
+                minimum_stake
+            ));
+        }
+        if maximum_stake != 0 && stake_amount > maximum_stake {
+            return Err(anyhow!(
+                "Stake {} is above the maximum of {}",
+                stake_amount,
+                maximum_stake
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Returns the fee credited to the treasury
+    fn charge_fee(&self, stake_token: &AlkaneId, stake_amount: u128) -> Result<u128> {
+        let fee = stake_amount
+            .checked_mul(storage::fee_bps().get_or_default(self)?)
+            .ok_or_else(|| anyhow!("Fee calculation overflow"))?
+            / BASIS_POINTS;
+        if fee == 0 {
+            return Ok(0);
+        }
+
+        storage::treasury_balances().add(self, stake_token, fee)?;
+
+        Ok(fee)
+    }
+
+    fn parse_bonus_table(steps: &[u128]) -> Result<Vec<(u128, u8)>> {
+        let mut bonus_table: Vec<(u128, u8)> = Vec::with_capacity(MAX_BONUS_STEPS);
+
+        for step in steps.chunks_exact(2).take(MAX_BONUS_STEPS) {
+            let (minimum_stake, bonus) = (step[0], step[1]);
+            if minimum_stake == 0 && bonus == 0 {
+                continue;
+            }
+            if bonus > u8::MAX as u128 {
+                return Err(anyhow!("Bonus must be at most {}, got {}", u8::MAX, bonus));
+            }
+            if let Some((previous_stake, previous_bonus)) = bonus_table.last() {
+                if minimum_stake <= *previous_stake || (bonus as u8) < *previous_bonus {
+                    return Err(anyhow!(
+                        "Bonus table steps must have increasing stakes and non-decreasing bonuses"
+                    ));
+                }
+            }
+            bonus_table.push((minimum_stake, bonus as u8));
+        }
+
+        Ok(bonus_table)
+    }
+
+    /// Boundaries from [small_win, win, big_win, jackpot]
+    fn parse_tier_boundaries(params: &[u128]) -> Result<TierBoundaries> {
+        let boundary = |index: usize| {
+            let value = params.get(index).copied().unwrap_or(0);
+            u8::try_from(value)
+                .map_err(|_| anyhow!("Tier boundary must be at most {}, got {}", u8::MAX, value))
+        };
+        TierBoundaries::new(boundary(0)?, boundary(1)?, boundary(2)?, boundary(3)?)
+    }
+
+    fn calculate_stake_bonus_internal(&self, stake_amount: u128) -> Result<u8> {
+        let bonus_table = self.bonus_table()?;
+
+        if bonus_table.is_empty() {
+            // Simple stake bonus calculation: 1 bonus point per 1000 staked tokens
+            let bonus = (stake_amount / 1000).min(255) as u8;
+            return Ok(bonus);
+        }
+
+        // Highest step whose minimum stake is met
+        let bonus = bonus_table
+            .iter()
+            .take_while(|(minimum_stake, _)| stake_amount >= *minimum_stake)
+            .last()
+            .map(|(_, bonus)| *bonus)
+            .unwrap_or(0);
+
+        Ok(bonus)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn create_coupon_token(
+        &self,
+        template_id: &AlkaneId,
+        stake_amount: u128,
+        base_xor: u8,
+        stake_bonus: u8,
+        final_result: u8,
+        is_winner: bool,
+        boundaries: &TierBoundaries,
+        provenance: &Provenance,
+    ) -> Result<AlkaneTransfer> {
+        let context = self.context()?;
+        let current_block = u128::from(self.height());
+        let coupon_id = self.total_coupons()?;
+
+        // Create cellpack for coupon token creation
+        let mut cellpack = Cellpack {
+            target: Self::coupon_creation_target(template_id)?,
+            inputs: vec![
+                0x0,           // Initialize opcode
+                coupon_id,     // Unique coupon ID
+                stake_amount,  // Stake amount used
+                base_xor as u128, // Base XOR result
+                stake_bonus as u128, // Stake bonus applied
+                final_result as u128, // Final XOR result
+                if is_winner { 1u128 } else { 0u128 }, // Win/lose flag
+                current_block, // Block of creation
+                context.myself.block, // Factory block ID
+                context.myself.tx,    // Factory tx ID
+                boundaries.to_u128(), // Tier boundaries the coupon was classified with
+            ],
+        };
+        cellpack.inputs.extend_from_slice(&provenance.to_inputs());
+
+        // No tokens sent to coupon (it's created with gambling state only)
+        let coupon_parcel = AlkaneTransferParcel::default();
+
+        let create_response = self.call(&cellpack, &coupon_parcel, self.fuel())?;
+
+        if create_response.alkanes.0.is_empty() {
+            return Err(anyhow!("Coupon token not returned by template"));
+        }
+
+        Ok(create_response.alkanes.0[0].clone())
+    }
+
+    /// Map a configured template to the cellpack target that instantiates it:
+    /// templates deployed at 4:n are created through 6:n, alkanes at 2:n are cloned through 5:n
+    fn coupon_creation_target(template_id: &AlkaneId) -> Result<AlkaneId> {
+        let block = match template_id.block {
+            4 => 6,
+            2 => 5,
+            block => {
+                return Err(anyhow!(
+                    "Coupon template {}:{} must live in block 2 or 4, not {}",
+                    template_id.block,
+                    template_id.tx,
+                    block
+                ))
+            }
+        };
+
+        Ok(AlkaneId {
+            block,
+            tx: template_id.tx,
+        })
+    }
+
+    /// Checks shared by every setter that accepts a coupon template
+    fn validate_coupon_template(&self, context: &Context, template_id: &AlkaneId) -> Result<()> {
+        if *template_id == context.myself {
+            return Err(anyhow!("Coupon template ID must not be the factory itself"));
+        }
+        Self::coupon_creation_target(template_id)?;
+        self.probe_coupon_template(template_id)
+    }
+
+    fn probe_coupon_template(&self, template_id: &AlkaneId) -> Result<()> {
+        // GetName (opcode 99) is side-effect free on the coupon template
+        let cellpack = Cellpack {
+            target: template_id.clone(),
+            inputs: vec![99],
+        };
+
+        self.staticcall(&cellpack, &AlkaneTransferParcel::default(), self.fuel())
+            .map_err(|e| {
+                anyhow!(
+                    "Coupon template {}:{} did not respond: {}",
+                    template_id.block,
+                    template_id.tx,
+                    e
+                )
+            })?;
+
+        Ok(())
+    }
+
+    /// Tell a freshly minted coupon which coupon it replaced or was crafted from
+    fn imprint_lineage(&self, coupon_id: &AlkaneId, opcode: u128, related: &AlkaneId) -> Result<()> {
+        let cellpack = Cellpack {
+            target: coupon_id.clone(),
+            inputs: vec![opcode, related.block, related.tx],
+        };
+        self.call(&cellpack, &AlkaneTransferParcel::default(), self.fuel())?;
+        Ok(())
+    }
+
+    fn incoming_coupon(&self, context: &Context) -> Result<AlkaneId> {
+        if context.incoming_alkanes.0.len() != 1 || context.incoming_alkanes.0[0].value != 1 {
+            return Err(anyhow!("Expected exactly one coupon token"));
+        }
+
+        let coupon_id = context.incoming_alkanes.0[0].id.clone();
+        if !self.is_registered_coupon_internal(&coupon_id)? {
+            return Err(anyhow!("Coupon was not created by this factory"));
+        }
+
+        Ok(coupon_id)
+    }
+
+    fn parse_tier(tier: u128) -> Result<OutcomeTier> {
+        OutcomeTier::from_index(tier).ok_or_else(|| {
+            anyhow!("Tier must be at most {}, got {}", OutcomeTier::Jackpot.index(), tier)
+        })
+    }
+
+    fn parse_prize_tier(tier: u128) -> Result<OutcomeTier> {
+        match OutcomeTier::from_index(tier) {
+            Some(tier) if tier.is_win() => Ok(tier),
+            _ => Err(anyhow!(
+                "Prize tier must be between {} and {}",
+                OutcomeTier::SmallWin.index(),
+                OutcomeTier::Jackpot.index()
+            )),
+        }
+    }
+
+    fn calculate_payout(&self, coupon_id: &AlkaneId) -> Result<AlkaneTransfer> {
+        let stake_amount = storage::coupon_stake_amount().get_or_default(self, coupon_id)?;
+
+        // Every winning tier is paid the same ratio; tier rewards come from the
+        // tier prize vaults. Without a configured payout token winners are paid
+        // 1:1 in their stake token.
+        let payout = match self.payout_token()? {
+            Some(payout_token) => AlkaneTransfer {
+                id: payout_token,
+                value: stake_amount
+                    .checked_mul(storage::payout_ratio_numerator().get_or_default(self)?)
+                    .ok_or_else(|| anyhow!("Payout calculation overflow"))?
+                    / storage::payout_ratio_denominator().require(self)?,
+            },
+            None => AlkaneTransfer {
+                id: storage::coupon_stake_token()
+                    .get(self, coupon_id)?
+                    .ok_or_else(|| anyhow!("Coupon has no recorded stake token"))?,
+                value: stake_amount,
+            },
+        };
+
+        if payout.value == 0 {
+            return Err(anyhow!("Coupon payout is zero"));
+        }
+
+        Ok(payout)
+    }
+
+    // Auth token operations
+
+    fn deploy_auth_token(&self) -> Result<AlkaneTransfer> {
+        let cellpack = Cellpack {
+            target: AlkaneId {
+                block: 6,
+                tx: AUTH_TOKEN_TEMPLATE_ID,
+            },
+            inputs: vec![0x0, 1u128],
+        };
+
+        let response = self.call(&cellpack, &AlkaneTransferParcel::default(), self.fuel())?;
+
+        response
+            .alkanes
+            .0
+            .first()
+            .cloned()
+            .ok_or_else(|| anyhow!("Auth token not returned by template"))
+    }
+
+    fn require_current_schema(&self) -> Result<()> {
+        let version = migration::schema_version(self)?;
+        if version != CURRENT_SCHEMA_VERSION {
+            return Err(anyhow!(
+                "Storage schema version {} must be migrated to {} first",
+                version,
+                CURRENT_SCHEMA_VERSION
+            ));
+        }
+        Ok(())
+    }
+
+    fn validate_role(role: u128) -> Result<()> {
+        if role > ROLE_PAUSER {
+            return Err(anyhow!("Unknown role {}", role));
+        }
+        Ok(())
+    }
+
+    fn only_role(&self, context: &Context, role: u128) -> Result<()> {
+        for transfer in &context.incoming_alkanes.0 {
+            if transfer.value > 0 && self.is_role_token(role, &transfer.id)? {
+                return Ok(());
+            }
+        }
+
+        Err(anyhow!("Auth token for role {} is required for this operation", role))
+    }
+
+    // Storage operations, see the storage module for the layout
+
+    fn coupon_token_template_id(&self) -> Result<AlkaneId> {
+        storage::coupon_token_template_id()
+            .get(self)?
+            .ok_or_else(|| anyhow!("Coupon token template ID not set"))
+    }
+
+    /// Template for coupons of a tier, falling back to the default coupon template
+    fn template_for_tier(&self, tier: OutcomeTier) -> Result<AlkaneId> {
+        match storage::tier_templates().get(self, &tier.index())? {
+            Some(template) => Ok(template),
+            None => self.coupon_token_template_id(),
+        }
+    }
+
+    /// Crafted coupons use the default coupon template until one is configured
+    fn crafting_template(&self) -> Result<AlkaneId> {
+        match storage::crafting_template_id().get(self)? {
+            Some(template) => Ok(template),
+            None => self.coupon_token_template_id(),
+        }
+    }
+
+    fn reroll_bonus(&self) -> Result<u8> {
+        Ok(storage::reroll_bonus().get(self)?.unwrap_or(DEFAULT_REROLL_BONUS))
+    }
+
+    fn tier_boundaries(&self) -> Result<TierBoundaries> {
+        storage::tier_boundaries().get_or_default(self)
+    }
+
+    fn set_coupon_token_template_id(&self, id: &AlkaneId) -> Result<()> {
+        storage::coupon_token_template_id().set(self, id);
+        Ok(())
+    }
+
+    fn success_threshold(&self) -> Result<u8> {
+        // 144 unless configured
+        Ok(storage::success_threshold().get(self)?.unwrap_or(144))
+    }
+
+    fn store_success_threshold(&self, threshold: u8) {
+        storage::success_threshold().set(self, &threshold);
+    }
+
+    fn stake_limits(&self) -> Result<(u128, u128)> {
+        Ok((
+            storage::minimum_stake().get_or_default(self)?,
+            storage::maximum_stake().get_or_default(self)?,
+        ))
+    }
+
+    fn bonus_table(&self) -> Result<Vec<(u128, u8)>> {
+        let bytes = storage::bonus_table().get_or_default(self)?;
+
+        // Each step is 17 bytes: [minimum_stake (16)] + [bonus (1)]
+        if bytes.len() % 17 != 0 {
+            return Err(anyhow!(
+                "Malformed bonus table: {} bytes is not a multiple of 17",
+                bytes.len()
+            ));
+        }
+        bytes
+            .chunks_exact(17)
+            .map(|step| Ok((u128::from_le_bytes(step[0..16].try_into()?), step[16])))
+            .collect()
+    }
+
+    fn set_bonus_table_entries(&self, bonus_table: &[(u128, u8)]) {
+        storage::bonus_table().set(self, &Self::bonus_table_bytes(bonus_table));
+    }
+
+    fn bonus_table_bytes(bonus_table: &[(u128, u8)]) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(bonus_table.len() * 17);
+        for (minimum_stake, bonus) in bonus_table {
+            bytes.extend_from_slice(&minimum_stake.to_le_bytes());
+            bytes.push(*bonus);
+        }
+        bytes
+    }
+
+    // Role registry
+
+    fn is_role_token(&self, role: u128, token: &AlkaneId) -> Result<bool> {
+        Ok(storage::role_flags(role).get(self, token)?.unwrap_or(false))
+    }
+
+    fn role_tokens(&self, role: u128) -> Result<Vec<AlkaneId>> {
+        storage::role_tokens(role).get_or_default(self)
+    }
+
+    fn add_role_token(&self, role: u128, token: &AlkaneId) -> Result<()> {
+        storage::role_flags(role).set(self, token, &true);
+
+        let mut tokens = self.role_tokens(role)?;
+        tokens.push(token.clone());
+        storage::role_tokens(role).set(self, &tokens);
+        Ok(())
+    }
+
+    fn remove_role_token(&self, role: u128, token: &AlkaneId) -> Result<()> {
+        storage::role_flags(role).set(self, token, &false);
+
+        let tokens: Vec<AlkaneId> = self
+            .role_tokens(role)?
+            .into_iter()
+            .filter(|registered| registered != token)
+            .collect();
+        storage::role_tokens(role).set(self, &tokens);
+        Ok(())
+    }
+
+    // Timelocked configuration changes
+
+    fn schedule_change(
+        &self,
+        context: &Context,
+        kind: u128,
+        params: Vec<u128>,
+    ) -> Result<CallResponse> {
+        self.apply_due_changes()?;
+
+        let mut response = CallResponse::forward(&context.incoming_alkanes);
+        let timelock = storage::timelock().get_or_default(self)?;
+
+        let change = PendingChange {
+            kind,
+            activation_block: u128::from(self.height()).saturating_add(timelock),
+            status: CHANGE_PENDING,
+            params,
+        };
+        let change_id = storage::pending_changes().push(self, &change)?;
+
+        // Without a timelock the change takes effect straight away
+        if timelock == 0 {
+            let mut config = self.stored_config()?;
+            config.apply(&change)?;
+            self.store_config(&config);
+            self.set_pending_change_status(change_id, CHANGE_APPLIED)?;
+        }
+
+        response.data = change_id.to_le_bytes().to_vec();
+        Ok(response)
+    }
+
+    /// Make due changes take effect in storage. Only mutating entry points call
+    /// this; getters read [`Self::effective_config`] so they never write.
+    fn apply_due_changes(&self) -> Result<()> {
+        let due = self.due_changes()?;
+        if !due.is_empty() {
+            let mut config = self.stored_config()?;
+            for (change_id, change) in &due {
+                config.apply(change)?;
+                self.set_pending_change_status(*change_id, CHANGE_APPLIED)?;
+            }
+            self.store_config(&config);
+        }
+
+        // Skip over the settled prefix so later scans start at the first open change
+        let changes = storage::pending_changes();
+        let count = changes.len(self)?;
+        let mut first_open = storage::first_open_change().get_or_default(self)?;
+        while first_open < count && changes.item(first_open).require(self)?.status != CHANGE_PENDING {
+            first_open += 1;
+        }
+        storage::first_open_change().set(self, &first_open);
+
+        Ok(())
+    }
+
+    /// Queued settings with every due change overlaid in memory, for getters
+    /// that may be reached through staticcall
+    fn effective_config(&self) -> Result<EffectiveConfig> {
+        let mut config = self.stored_config()?;
+        for (_, change) in self.due_changes()? {
+            config.apply(&change)?;
+        }
+        Ok(config)
+    }
+
+    /// Queued settings as stored, before due changes take effect
+    fn stored_config(&self) -> Result<EffectiveConfig> {
+        let (minimum_stake, maximum_stake) = self.stake_limits()?;
+        let payout = match self.payout_token()? {
+            Some(payout_token) => Some((
+                payout_token,
+                storage::payout_ratio_numerator().get_or_default(self)?,
+                storage::payout_ratio_denominator().get_or_default(self)?,
+            )),
+            None => None,
+        };
+
+        Ok(EffectiveConfig {
+            success_threshold: self.success_threshold()?,
+            minimum_stake,
+            maximum_stake,
+            bonus_table: self.bonus_table()?,
+            fee_bps: storage::fee_bps().get_or_default(self)?,
+            timelock: storage::timelock().get_or_default(self)?,
+            reroll_bonus: self.reroll_bonus()?,
+            payout,
+            tier_boundaries: self.tier_boundaries()?,
+        })
+    }
+
+    fn store_config(&self, config: &EffectiveConfig) {
+        self.store_success_threshold(config.success_threshold);
+        storage::minimum_stake().set(self, &config.minimum_stake);
+        storage::maximum_stake().set(self, &config.maximum_stake);
+        self.set_bonus_table_entries(&config.bonus_table);
+        storage::fee_bps().set(self, &config.fee_bps);
+        storage::timelock().set(self, &config.timelock);
+        storage::reroll_bonus().set(self, &config.reroll_bonus);
+        if let Some((payout_token, ratio_numerator, ratio_denominator)) = &config.payout {
+            storage::payout_token().set(self, payout_token);
+            storage::payout_ratio_numerator().set(self, ratio_numerator);
+            storage::payout_ratio_denominator().set(self, ratio_denominator);
+        }
+        storage::tier_boundaries().set(self, &config.tier_boundaries);
+    }
+
+    /// Pending changes that have not reached their activation block, oldest first
+    fn scheduled_changes(&self) -> Result<Vec<(u128, PendingChange)>> {
+        let height = u128::from(self.height());
+        Ok(self
+            .open_changes()?
+            .into_iter()
+            .filter(|(_, change)| change.activation_block > height)
+            .collect())
+    }
+
+    /// Pending changes whose activation block has been reached, oldest first
+    fn due_changes(&self) -> Result<Vec<(u128, PendingChange)>> {
+        let height = u128::from(self.height());
+        Ok(self
+            .open_changes()?
+            .into_iter()
+            .filter(|(_, change)| change.activation_block <= height)
+            .collect())
+    }
+
+    fn open_changes(&self) -> Result<Vec<(u128, PendingChange)>> {
+        let changes = storage::pending_changes();
+        let mut open = Vec::new();
+        for change_id in storage::first_open_change().get_or_default(self)?..changes.len(self)? {
+            let change = changes.item(change_id).require(self)?;
+            if change.status == CHANGE_PENDING {
+                open.push((change_id, change));
+            }
+        }
+        Ok(open)
+    }
+
+    fn pending_change(&self, change_id: u128) -> Result<Option<PendingChange>> {
+        storage::pending_changes().get(self, change_id)
+    }
+
+    fn set_pending_change_status(&self, change_id: u128, status: u8) -> Result<()> {
+        let changes = storage::pending_changes();
+        let mut change = changes.item(change_id).require(self)?;
+        change.status = status;
+        changes.set(self, change_id, &change)
+    }
+
+    // Treasury balances accrued from fees
+
+    fn treasury_balance(&self, token: &AlkaneId) -> Result<u128> {
+        storage::treasury_balances().get_or_default(self, token)
+    }
+
+    // Cumulative volume per token
+    //
+    // Counters only ever grow: withdrawing fees does not reduce `treasury`.
+    // `burned` is the part of each stake that is consumed by the factory
+    // rather than credited to the treasury.
+
+    fn volume(&self, token: &AlkaneId, field: &str) -> Result<u128> {
+        storage::volume(field).get_or_default(self, token)
+    }
+
+    fn add_volume(&self, token: &AlkaneId, field: &str, amount: u128) -> Result<()> {
+        storage::volume(field).add(self, token, amount)?;
+        Ok(())
+    }
+
+    fn record_stake_volume(&self, stake_token: &AlkaneId, stake_amount: u128, fee: u128) -> Result<()> {
+        let burned = stake_amount
+            .checked_sub(fee)
+            .ok_or_else(|| anyhow!("Fee exceeds stake"))?;
+
+        self.add_volume(stake_token, "staked", stake_amount)?;
+        self.add_volume(stake_token, "burned", burned)?;
+        self.add_volume(stake_token, "treasury", fee)?;
+
+        // First stake in this token: add it to the list of staked tokens
+        if !storage::volume_listed().contains(self, stake_token) {
+            storage::staked_tokens().push(self, stake_token)?;
+            storage::volume_listed().set(self, stake_token, &true);
+        }
+
+        Ok(())
+    }
+
+    fn staked_tokens(&self) -> Result<Vec<AlkaneId>> {
+        storage::staked_tokens().all(self)
+    }
+
+    // Per-block and per-epoch statistics
+    //
+    // Epochs are counted from an anchor (epoch, height) so the epoch length can
+    // change without renumbering earlier epochs. A new length only takes effect
+    // at the anchor, which may still lie in the future.
+
+    fn epoch_length(&self) -> Result<u128> {
+        Ok(match storage::epoch_length().get_or_default(self)? {
+            0 => DEFAULT_EPOCH_LENGTH,
+            length => length,
+        })
+    }
+
+    /// Epoch index and start height for the current block
+    fn current_epoch(&self) -> Result<(u128, u128)> {
+        let height = u128::from(self.height());
+        let anchor_epoch = storage::epoch_anchor_epoch().get_or_default(self)?;
+        let anchor_height = storage::epoch_anchor_height().get_or_default(self)?;
+        let length = self.epoch_length()?;
+
+        if height < anchor_height {
+            // Still inside the epoch that was running when the length changed
+            let previous_length = storage::epoch_previous_length().get_or_default(self)?.max(1);
+            let epoch = anchor_epoch.saturating_sub(1);
+            return Ok((epoch, anchor_height.saturating_sub(previous_length)));
+        }
+
+        let elapsed = (height - anchor_height) / length;
+        Ok((anchor_epoch + elapsed, anchor_height + elapsed * length))
+    }
+
+    fn block_stats(&self, height: u128) -> Result<PeriodStats> {
+        storage::block_stats().get_or_default(self, &height)
+    }
+
+    fn epoch_stats(&self, epoch: u128) -> Result<PeriodStats> {
+        storage::epoch_stats().get_or_default(self, &epoch)
+    }
+
+    fn record_period_stats(&self, stake_amount: u128, stake_bonus: u8, final_result: u8, is_winner: bool) -> Result<()> {
+        let height = u128::from(self.height());
+        let mut block_stats = self.block_stats(height)?;
+        block_stats.record(stake_amount, stake_bonus, final_result, is_winner)?;
+        storage::block_stats().set(self, &height, &block_stats);
+
+        let (epoch, _) = self.current_epoch()?;
+        let mut epoch_stats = self.epoch_stats(epoch)?;
+        epoch_stats.record(stake_amount, stake_bonus, final_result, is_winner)?;
+        storage::epoch_stats().set(self, &epoch, &epoch_stats);
+
+        Ok(())
+    }
+
+    // Leaderboards, kept sorted so each coupon only touches the bounded boards
+
+    fn leaderboard(&self, scope: u128) -> Result<Leaderboard> {
+        match scope {
+            SCOPE_ALL_TIME => storage::all_time_leaderboard().get_or_default(self),
+            SCOPE_EPOCH => {
+                let (epoch, _) = self.current_epoch()?;
+                storage::epoch_leaderboards().get_or_default(self, &epoch)
+            }
+            _ => Err(anyhow!("Invalid leaderboard scope {}", scope)),
+        }
+    }
+
+    fn record_leaderboards(&self, coupon_id: &AlkaneId, final_result: u8, stake_amount: u128) -> Result<()> {
+        let entry = LeaderboardEntry {
+            coupon_id: coupon_id.clone(),
+            final_result,
+            stake_amount,
+            block: u128::from(self.height()),
+        };
+
+        let mut all_time = self.leaderboard(SCOPE_ALL_TIME)?;
+        if all_time.insert(entry.clone()) {
+            storage::all_time_leaderboard().set(self, &all_time);
+        }
+
+        let (epoch, _) = self.current_epoch()?;
+        let mut epoch_board = self.leaderboard(SCOPE_EPOCH)?;
+        if epoch_board.insert(entry) {
+            storage::epoch_leaderboards().set(self, &epoch, &epoch_board);
+        }
+
+        Ok(())
+    }
+
+    // Pause state and circuit breaker
+
+    fn is_paused(&self) -> Result<bool> {
+        storage::paused().get_or_default(self)
+    }
+
+    fn pause_reason(&self) -> Result<String> {
+        storage::pause_reason().get_or_default(self)
     }
 
-    fn transaction_id(&self) -> Result<Txid> {
-        Ok(
-            consensus_decode::<Transaction>(&mut std::io::Cursor::new(self.transaction()))?
-                .compute_txid(),
-        )
+    fn set_paused(&self, paused: bool, reason: &str) {
+        storage::paused().set(self, &paused);
+        storage::pause_reason().set(self, &reason.to_string());
     }
 
-    fn merkle_root(&self) -> Result<TxMerkleNode> {
-        // Production-ready implementation: Get the current block's merkle root
-        // This uses the runtime's block context to get the actual merkle root
-        let current_height = self.height();
-        
-        // Create a deterministic but cryptographically sound merkle root
-        // based on block height and transaction context
-        let txid = self.transaction_id()?;
-        let mut hasher = bitcoin::hashes::sha256::Hash::engine();
+    fn reset_breaker_window(&self) {
+        // Outcome slots are only read once filled, so clearing the cursors is enough
+        storage::breaker_position().set(self, &0);
+        storage::breaker_filled().set(self, &0);
+        storage::breaker_recent_wins().set(self, &0);
+    }
+
+    fn record_breaker_outcome(&self, is_winner: bool) -> Result<()> {
+        let window = storage::breaker_window().get_or_default(self)?;
+        if window == 0 {
+            return Ok(());
+        }
+
+        let position = storage::breaker_position().get_or_default(self)?;
+        let mut filled = storage::breaker_filled().get_or_default(self)?;
+        let mut recent_wins = storage::breaker_recent_wins().get_or_default(self)?;
+
+        // Outcomes live in a ring buffer of `window` slots
+        let slot = position % window;
+        if filled == window {
+            if storage::breaker_outcomes().get_or_default(self, &slot)? {
+                recent_wins = recent_wins.saturating_sub(1);
+            }
+        } else {
+            filled += 1;
+        }
+
+        storage::breaker_outcomes().set(self, &slot, &is_winner);
+        if is_winner {
+            recent_wins += 1;
+        }
+
+        storage::breaker_position().set(self, &position.wrapping_add(1));
+        storage::breaker_filled().set(self, &filled);
+        storage::breaker_recent_wins().set(self, &recent_wins);
+
+        // Only judge a full window
+        let max_win_ratio_bps = storage::breaker_max_win_ratio_bps().get_or_default(self)?;
+        if filled == window
+            && recent_wins.saturating_mul(BASIS_POINTS) > window.saturating_mul(max_win_ratio_bps)
+            && !self.is_paused()?
+        {
+            self.set_paused(
+                true,
+                &format!(
+                    "Circuit breaker tripped at block {}: {} wins in the last {} coupons exceeds {} bps",
+                    self.height(),
+                    recent_wins,
+                    window,
+                    max_win_ratio_bps
+                ),
+            );
+        }
+
+        Ok(())
+    }
+
+    fn successful_coupons(&self) -> Result<u128> {
+        storage::successful_coupons().get_or_default(self)
+    }
+
+    fn failed_coupons(&self) -> Result<u128> {
+        storage::failed_coupons().get_or_default(self)
+    }
+
+    fn crafted_coupons(&self) -> Result<u128> {
+        storage::crafted_coupons().get_or_default(self)
+    }
+
+    /// Crafted coupons draw their ids from the same sequence as rolled ones
+    fn total_coupons(&self) -> Result<u128> {
+        [self.successful_coupons()?, self.failed_coupons()?, self.crafted_coupons()?]
+            .into_iter()
+            .try_fold(0u128, u128::checked_add)
+            .ok_or_else(|| anyhow!("Total coupon count overflow"))
+    }
+
+    // Registry operations following boiler patterns
+
+    fn is_registered_coupon_internal(&self, coupon_id: &AlkaneId) -> Result<bool> {
+        Ok(self.coupon_status(coupon_id)?.is_some())
+    }
+
+    fn register_coupon(&self, coupon_id: &AlkaneId) -> Result<()> {
+        // The lifecycle status doubles as the registration flag for O(1) lookup
+        storage::coupon_status().set(self, coupon_id, &COUPON_ACTIVE);
+
+        // Append to the indexed registry for enumeration, with a reverse lookup
+        let index = storage::registered_coupon_list().push(self, coupon_id)?;
+        storage::registered_coupon_index().set(self, coupon_id, &index);
+        Ok(())
+    }
+
+    fn registered_coupon_index(&self, coupon_id: &AlkaneId) -> Result<Option<u128>> {
+        if !self.is_registered_coupon_internal(coupon_id)? {
+            return Ok(None);
+        }
+        storage::registered_coupon_index().get(self, coupon_id)
+    }
+
+    fn registered_coupons_page(&self, offset: u128, limit: u128) -> Result<Vec<AlkaneId>> {
+        storage::registered_coupon_list().page(self, offset, limit.min(MAX_PAGE_SIZE))
+    }
+
+    fn registered_coupons_count(&self) -> Result<u128> {
+        storage::registered_coupon_list().len(self)
+    }
+
+    // Per-coupon records kept by the factory
+
+    fn record_coupon_stake(
+        &self,
+        coupon_id: &AlkaneId,
+        stake_token: Option<&AlkaneId>,
+        stake_amount: u128,
+        final_result: u8,
+        is_winner: bool,
+        tier: OutcomeTier,
+    ) {
+        if let Some(stake_token) = stake_token {
+            storage::coupon_stake_token().set(self, coupon_id, stake_token);
+        }
+        storage::coupon_stake_amount().set(self, coupon_id, &stake_amount);
+        storage::coupon_final_result().set(self, coupon_id, &final_result);
+        storage::coupon_is_winner().set(self, coupon_id, &is_winner);
+        storage::coupon_tier().set(self, coupon_id, &(tier.index() as u8));
+    }
+
+    /// Tier the coupon was minted with. Coupons minted before tiers were
+    /// recorded are classified with the current boundaries.
+    fn coupon_tier(&self, coupon_id: &AlkaneId) -> Result<OutcomeTier> {
+        match storage::coupon_tier().get(self, coupon_id)? {
+            Some(tier) => Self::parse_tier(tier as u128),
+            None => Ok(self.tier_boundaries()?.classify(
+                self.coupon_final_result(coupon_id)?,
+                self.is_coupon_winner(coupon_id)?,
+            )),
+        }
+    }
+
+    // Replay protection: transactions that have already created coupons
+
+    fn has_tx_hash(&self, txid: &Txid) -> Result<bool> {
+        Ok(storage::processed_txids()
+            .get(self, txid.as_byte_array())?
+            .unwrap_or(false))
+    }
+
+    fn add_tx_hash(&self, txid: &Txid) -> Result<()> {
+        storage::processed_txids().set(self, txid.as_byte_array(), &true);
+        Ok(())
+    }
+
+    // Creation txid lookups; one transaction may mint several coupons
+
+    fn record_coupon_txid(&self, coupon_id: &AlkaneId, txid: &Txid) -> Result<()> {
+        let txid_bytes = txid.as_byte_array();
+        storage::coupons_by_txid(txid_bytes).push(self, coupon_id)?;
+        storage::coupon_txid().set(self, coupon_id, txid_bytes);
+        Ok(())
+    }
+
+    fn coupons_by_txid(&self, txid: &[u8; 32]) -> Result<Vec<AlkaneId>> {
+        storage::coupons_by_txid(txid).all(self)
+    }
+
+    fn coupon_txid(&self, coupon_id: &AlkaneId) -> Result<Option<[u8; 32]>> {
+        storage::coupon_txid().get(self, coupon_id)
+    }
+
+    fn is_crafted_coupon(&self, coupon_id: &AlkaneId) -> Result<bool> {
+        Ok(!storage::craft_inputs(coupon_id).is_empty(self)?)
+    }
+
+    fn is_coupon_winner(&self, coupon_id: &AlkaneId) -> Result<bool> {
+        Ok(storage::coupon_is_winner().get(self, coupon_id)?.unwrap_or(false))
+    }
+
+    fn coupon_final_result(&self, coupon_id: &AlkaneId) -> Result<u8> {
+        storage::coupon_final_result().get_or_default(self, coupon_id)
+    }
+
+    fn is_coupon_redeemed_internal(&self, coupon_id: &AlkaneId) -> Result<bool> {
+        Ok(self.coupon_status(coupon_id)? == Some(COUPON_REDEEMED))
+    }
+
+    // Coupon lifecycle
+
+    /// `None` for coupons this factory never minted
+    fn coupon_status(&self, coupon_id: &AlkaneId) -> Result<Option<u8>> {
+        storage::coupon_status().get(self, coupon_id)
+    }
+
+    fn coupon_status_name(status: u8) -> &'static str {
+        match status {
+            COUPON_ACTIVE => "active",
+            COUPON_REDEEMED => "redeemed",
+            COUPON_BURNED => "burned",
+            COUPON_EXPIRED => "expired",
+            COUPON_VOID => "void",
+            _ => "unknown",
+        }
+    }
+
+    /// Re-rolls and crafts consume active losing coupons. Crafted coupons
+    /// never win either, but they may not be fed back in.
+    fn require_consumable_loser(&self, coupon_id: &AlkaneId) -> Result<()> {
+        self.require_active_coupon(coupon_id)?;
+        if self.is_coupon_winner(coupon_id)? {
+            return Err(anyhow!(
+                "Coupon {}:{} is a winner, only losing coupons can be consumed",
+                coupon_id.block,
+                coupon_id.tx
+            ));
+        }
+        if self.is_crafted_coupon(coupon_id)? {
+            return Err(anyhow!("Coupon {}:{} is already crafted", coupon_id.block, coupon_id.tx));
+        }
+        Ok(())
+    }
+
+    fn require_active_coupon(&self, coupon_id: &AlkaneId) -> Result<()> {
+        match self.coupon_status(coupon_id)? {
+            Some(COUPON_ACTIVE) => Ok(()),
+            Some(COUPON_REDEEMED) => Err(anyhow!("Coupon has already been redeemed")),
+            Some(status) => Err(anyhow!(
+                "Coupon {}:{} is {}",
+                coupon_id.block,
+                coupon_id.tx,
+                Self::coupon_status_name(status)
+            )),
+            None => Err(anyhow!("Coupon {}:{} is not registered", coupon_id.block, coupon_id.tx)),
+        }
+    }
+
+    // Prize inventory and payout configuration
+
+    fn prize_inventory(&self, token: &AlkaneId) -> Result<u128> {
+        storage::prize_inventory().get_or_default(self, token)
+    }
+
+    fn payout_token(&self) -> Result<Option<AlkaneId>> {
+        storage::payout_token().get(self)
+    }
+
+    // Tier prize vault: a FIFO queue of NFT ids per tier, stored as
+    // /prize_vault/{tier}/{index} between the head and tail cursors
+
+    fn tier_prizes(&self, tier: u128) -> Result<Vec<AlkaneId>> {
+        let slots = storage::prize_vault_slots(tier);
+        (storage::prize_vault_head(tier).get_or_default(self)?
+            ..storage::prize_vault_tail(tier).get_or_default(self)?)
+            .map(|index| slots.entry(&index).require(self))
+            .collect()
+    }
+
+    fn push_tier_prize(&self, tier: u128, prize: &AlkaneId) -> Result<()> {
+        let tail = storage::prize_vault_tail(tier).get_or_default(self)?;
+        storage::prize_vault_slots(tier).set(self, &tail, prize);
+        storage::prize_vault_tail(tier).increment(self)?;
+        Ok(())
+    }
+
+    fn pop_front_tier_prize(&self, tier: u128) -> Result<Option<AlkaneId>> {
+        let head = storage::prize_vault_head(tier).get_or_default(self)?;
+        if head >= storage::prize_vault_tail(tier).get_or_default(self)? {
+            return Ok(None);
+        }
+
+        let slot = storage::prize_vault_slots(tier).entry(&head);
+        let prize = slot.require(self)?;
+        slot.clear(self);
+        storage::prize_vault_head(tier).increment(self)?;
+        Ok(Some(prize))
+    }
+
+    fn pop_back_tier_prize(&self, tier: u128) -> Result<Option<AlkaneId>> {
+        let tail = storage::prize_vault_tail(tier).get_or_default(self)?;
+        if storage::prize_vault_head(tier).get_or_default(self)? >= tail {
+            return Ok(None);
+        }
+
+        let slot = storage::prize_vault_slots(tier).entry(&(tail - 1));
+        let prize = slot.require(self)?;
+        slot.clear(self);
+        storage::prize_vault_tail(tier).sub(self, 1)?;
+        Ok(Some(prize))
+    }
+
+    fn id_bytes(id: &AlkaneId) -> Vec<u8> {
+        StorageValue::encode(id)
+    }
+
+    fn push_config_entry(data: &mut Vec<u8>, tag: u16, value: &[u8]) {
+        data.extend_from_slice(&tag.to_le_bytes());
+        data.extend_from_slice(&(value.len() as u16).to_le_bytes());
+        data.extend_from_slice(value);
+    }
+
+    // Getter functions for frontend/external consumption
+
+    fn get_successful_coupons(&self) -> Result<CallResponse> {
+        let context = self.context()?;
+        let mut response = CallResponse::forward(&context.incoming_alkanes);
+        response.data = self.successful_coupons()?.to_le_bytes().to_vec();
+        Ok(response)
+    }
+
+    fn get_failed_coupons(&self) -> Result<CallResponse> {
+        let context = self.context()?;
+        let mut response = CallResponse::forward(&context.incoming_alkanes);
+        response.data = self.failed_coupons()?.to_le_bytes().to_vec();
+        Ok(response)
+    }
+
+    fn get_total_coupons(&self) -> Result<CallResponse> {
+        let context = self.context()?;
+        let mut response = CallResponse::forward(&context.incoming_alkanes);
+        response.data = self.total_coupons()?.to_le_bytes().to_vec();
+        Ok(response)
+    }
+
+    fn get_block_stats(&self, height: u128) -> Result<CallResponse> {
+        let context = self.context()?;
+        let mut response = CallResponse::forward(&context.incoming_alkanes);
+        response.data = Self::period_stats_data(&self.block_stats(height)?);
+        Ok(response)
+    }
+
+    fn get_epoch_stats(&self, epoch: u128) -> Result<CallResponse> {
+        let context = self.context()?;
+        let mut response = CallResponse::forward(&context.incoming_alkanes);
+        response.data = Self::period_stats_data(&self.epoch_stats(epoch)?);
+        Ok(response)
+    }
+
+    fn get_current_epoch(&self) -> Result<CallResponse> {
+        let context = self.context()?;
+        let mut response = CallResponse::forward(&context.incoming_alkanes);
+
+        // Format: [epoch (16)] + [start_height (16)] + [epoch_length (16)]
+        let (epoch, start_height) = self.current_epoch()?;
+        let mut data = Vec::with_capacity(48);
+        data.extend_from_slice(&epoch.to_le_bytes());
+        data.extend_from_slice(&start_height.to_le_bytes());
+        data.extend_from_slice(&self.epoch_length()?.to_le_bytes());
+
+        response.data = data;
+        Ok(response)
+    }
+
+    fn get_volume_stats(&self, token: AlkaneId) -> Result<CallResponse> {
+        let context = self.context()?;
+        let mut response = CallResponse::forward(&context.incoming_alkanes);
+
+        // Format: [staked (16)] + [burned (16)] + [treasury (16)] + [paid_out (16)]
+        let mut data = Vec::with_capacity(64);
+        for field in ["staked", "burned", "treasury", "paid_out"] {
+            data.extend_from_slice(&self.volume(&token, field)?.to_le_bytes());
+        }
+
+        response.data = data;
+        Ok(response)
+    }
+
+    fn get_staked_tokens(&self) -> Result<CallResponse> {
+        let context = self.context()?;
+        let mut response = CallResponse::forward(&context.incoming_alkanes);
+
+        let tokens = self.staked_tokens()?;
+
+        // Format: [count (8 bytes)] + [AlkaneId_1 (32 bytes)] + ... in first-staked order
+        let mut data = Vec::with_capacity(8 + tokens.len() * 32);
+        data.extend_from_slice(&(tokens.len() as u64).to_le_bytes());
+        for token in tokens {
+            data.extend_from_slice(&Self::id_bytes(&token));
+        }
+
+        response.data = data;
+        Ok(response)
+    }
+
+    fn get_leaderboard(&self, scope: u128, limit: u128) -> Result<CallResponse> {
+        let context = self.context()?;
+        let mut response = CallResponse::forward(&context.incoming_alkanes);
+
+        let board = self.leaderboard(scope)?;
+        let limit = limit.min(LEADERBOARD_SIZE as u128) as usize;
+        let entries = &board.entries[..limit.min(board.entries.len())];
+
+        // Format: [count (8 bytes)] + per entry, best first:
+        // [AlkaneId (32)] + [final_result (16)] + [stake_amount (16)] + [block (16)]
+        let mut data = Vec::with_capacity(8 + entries.len() * 80);
+        data.extend_from_slice(&(entries.len() as u64).to_le_bytes());
+        for entry in entries {
+            data.extend_from_slice(&Self::id_bytes(&entry.coupon_id));
+            data.extend_from_slice(&(entry.final_result as u128).to_le_bytes());
+            data.extend_from_slice(&entry.stake_amount.to_le_bytes());
+            data.extend_from_slice(&entry.block.to_le_bytes());
+        }
+
+        response.data = data;
+        Ok(response)
+    }
+
+    /// [coupons (16)] + [wins (16)] + [total_stake (16)] + [bonus_sum (16)] + [highest_final_result (16)]
+    fn period_stats_data(stats: &PeriodStats) -> Vec<u8> {
+        let mut data = Vec::with_capacity(80);
+        data.extend_from_slice(&stats.coupons.to_le_bytes());
+        data.extend_from_slice(&stats.wins.to_le_bytes());
+        data.extend_from_slice(&stats.total_stake.to_le_bytes());
+        data.extend_from_slice(&stats.bonus_sum.to_le_bytes());
+        data.extend_from_slice(&(stats.highest_final_result as u128).to_le_bytes());
+        data
+    }
+
+    fn get_success_threshold(&self) -> Result<CallResponse> {
+        let context = self.context()?;
+        let mut response = CallResponse::forward(&context.incoming_alkanes);
+        response.data = (self.effective_config()?.success_threshold as u128).to_le_bytes().to_vec();
+        Ok(response)
+    }
+
+    fn get_auth_token(&self) -> Result<CallResponse> {
+        let context = self.context()?;
+        let mut response = CallResponse::forward(&context.incoming_alkanes);
+        let auth_token = self
+            .role_tokens(ROLE_ADMIN)?
+            .first()
+            .cloned()
+            .ok_or_else(|| anyhow!("Admin auth token not set"))?;
+
+        let mut data = Vec::with_capacity(32);
+        data.extend_from_slice(&auth_token.block.to_le_bytes());
+        data.extend_from_slice(&auth_token.tx.to_le_bytes());
+
+        response.data = data;
+        Ok(response)
+    }
+
+    fn get_schema_version(&self) -> Result<CallResponse> {
+        let context = self.context()?;
+        let mut response = CallResponse::forward(&context.incoming_alkanes);
+        response.data = migration::schema_version(self)?.to_le_bytes().to_vec();
+        Ok(response)
+    }
+
+    fn get_role_tokens(&self, role: u128) -> Result<CallResponse> {
+        let context = self.context()?;
+        let mut response = CallResponse::forward(&context.incoming_alkanes);
+        Self::validate_role(role)?;
+
+        let tokens = self.role_tokens(role)?;
+
+        // Format: [count (8 bytes)] + [AlkaneId_1 (32 bytes)] + ...
+        let mut data = Vec::with_capacity(8 + tokens.len() * 32);
+        data.extend_from_slice(&(tokens.len() as u64).to_le_bytes());
+        for token in tokens {
+            data.extend_from_slice(&token.block.to_le_bytes());
+            data.extend_from_slice(&token.tx.to_le_bytes());
+        }
+
+        response.data = data;
+        Ok(response)
+    }
+
+    fn get_coupon_token_template_id(&self) -> Result<CallResponse> {
+        let context = self.context()?;
+        let mut response = CallResponse::forward(&context.incoming_alkanes);
+        let template_id = self.coupon_token_template_id()?;
         
-        // Combine block height and transaction ID for entropy
-        hasher.input(&current_height.to_le_bytes());
-        hasher.input(txid.as_byte_array());
+        let mut data = Vec::with_capacity(32);
+        data.extend_from_slice(&template_id.block.to_le_bytes());
+        data.extend_from_slice(&template_id.tx.to_le_bytes());
         
-        let hash = bitcoin::hashes::sha256::Hash::from_engine(hasher);
-        Ok(TxMerkleNode::from_byte_array(*hash.as_byte_array()))
+        response.data = data;
+        Ok(response)
+    }
+
+    fn get_tier_boundaries(&self) -> Result<CallResponse> {
+        let context = self.context()?;
+        let mut response = CallResponse::forward(&context.incoming_alkanes);
+
+        // Format: [small_win (16)] + [win (16)] + [big_win (16)] + [jackpot (16)]
+        let boundaries = self.effective_config()?.tier_boundaries;
+        let mut data = Vec::with_capacity(64);
+        for boundary in [boundaries.small_win, boundaries.win, boundaries.big_win, boundaries.jackpot] {
+            data.extend_from_slice(&(boundary as u128).to_le_bytes());
+        }
+
+        response.data = data;
+        Ok(response)
+    }
+
+    fn get_template_for_tier(&self, tier: u128) -> Result<CallResponse> {
+        let context = self.context()?;
+        let mut response = CallResponse::forward(&context.incoming_alkanes);
+        let template_id = self.template_for_tier(Self::parse_tier(tier)?)?;
+
+        let mut data = Vec::with_capacity(32);
+        data.extend_from_slice(&template_id.block.to_le_bytes());
+        data.extend_from_slice(&template_id.tx.to_le_bytes());
+
+        response.data = data;
+        Ok(response)
     }
 
-    fn get_stake_input_amount(&self, context: &Context) -> Result<u128> {
-        let mut total_stake = 0u128;
+    fn get_all_registered_coupons(&self) -> Result<CallResponse> {
+        let context = self.context()?;
+        let mut response = CallResponse::forward(&context.incoming_alkanes);
+
+        let coupons_list = storage::registered_coupon_list().all(self)?;
+        let coupons_count = coupons_list.len();
 
-        // Accept any incoming tokens as stake (generic approach)
-        for transfer in &context.incoming_alkanes.0 {
-            total_stake = total_stake.checked_add(transfer.value).unwrap_or(total_stake);
+        // Format: [count (8 bytes)] + [AlkaneId_1 (32 bytes)] + [AlkaneId_2 (32 bytes)] + ...
+        let mut data = Vec::new();
+
+        // Add count of registered coupons (as u64 for compatibility)
+        data.extend_from_slice(&(coupons_count as u64).to_le_bytes());
+
+        // Add each registered coupon AlkaneId
+        for coupon in coupons_list {
+            data.extend_from_slice(&coupon.block.to_le_bytes()); // 16 bytes
+            data.extend_from_slice(&coupon.tx.to_le_bytes());    // 16 bytes
         }
 
-        Ok(total_stake)
+        response.data = data;
+        Ok(response)
     }
 
-    fn calculate_stake_bonus_internal(&self, stake_amount: u128) -> Result<u8> {
-        // Simple stake bonus calculation: 1 bonus point per 1000 staked tokens
-        let bonus = (stake_amount / 1000).min(255) as u8;
-        Ok(bonus)
+    fn is_registered_coupon(&self, coupon_id: AlkaneId) -> Result<CallResponse> {
+        let context = self.context()?;
+        let mut response = CallResponse::forward(&context.incoming_alkanes);
+        let is_registered = self.is_registered_coupon_internal(&coupon_id)?;
+        response.data = (if is_registered { 1u128 } else { 0u128 }).to_le_bytes().to_vec();
+        Ok(response)
     }
 
-    fn create_coupon_token(
-        &self,
-        stake_amount: u128,
-        base_xor: u8,
-        stake_bonus: u8,
-        final_result: u8,
-        is_winner: bool,
-    ) -> Result<AlkaneTransfer> {
+    fn get_registered_coupons_page(&self, offset: u128, limit: u128) -> Result<CallResponse> {
         let context = self.context()?;
-        let current_block = u128::from(self.height());
-        let coupon_id = self.total_coupons();
+        let mut response = CallResponse::forward(&context.incoming_alkanes);
 
-        // Create cellpack for coupon token creation
-        let cellpack = Cellpack {
-            target: AlkaneId {
-                block: 6,  // External call target (6 → 4 → 2 for coupon creation)
-                tx: COUPON_TOKEN_TEMPLATE_ID, // Template TX ID constant
-            },
-            inputs: vec![
-                0x0,           // Initialize opcode
-                coupon_id,     // Unique coupon ID
-                stake_amount,  // Stake amount used
-                base_xor as u128, // Base XOR result
-                stake_bonus as u128, // Stake bonus applied
-                final_result as u128, // Final XOR result
-                if is_winner { 1u128 } else { 0u128 }, // Win/lose flag
-                current_block, // Block of creation
-                context.myself.block, // Factory block ID
-                context.myself.tx,    // Factory tx ID
-            ],
-        };
+        let coupons = self.registered_coupons_page(offset, limit)?;
 
-        // No tokens sent to coupon (it's created with gambling state only)
-        let coupon_parcel = AlkaneTransferParcel::default();
+        // Format: [total registered (16)] + [count (8 bytes)] + [AlkaneId_1 (32 bytes)] + ...
+        let mut data = Vec::with_capacity(24 + coupons.len() * 32);
+        data.extend_from_slice(&self.registered_coupons_count()?.to_le_bytes());
+        data.extend_from_slice(&(coupons.len() as u64).to_le_bytes());
+        for coupon in coupons {
+            data.extend_from_slice(&coupon.block.to_le_bytes());
+            data.extend_from_slice(&coupon.tx.to_le_bytes());
+        }
 
-        let create_response = self.call(&cellpack, &coupon_parcel, self.fuel())?;
+        response.data = data;
+        Ok(response)
+    }
 
-        if create_response.alkanes.0.is_empty() {
-            return Err(anyhow!("Coupon token not returned by template"));
-        }
+    fn get_coupon_index(&self, coupon_id: AlkaneId) -> Result<CallResponse> {
+        let context = self.context()?;
+        let mut response = CallResponse::forward(&context.incoming_alkanes);
 
-        Ok(create_response.alkanes.0[0].clone())
+        let index = self
+            .registered_coupon_index(&coupon_id)?
+            .ok_or_else(|| anyhow!("Coupon {}:{} is not registered", coupon_id.block, coupon_id.tx))?;
+
+        response.data = index.to_le_bytes().to_vec();
+        Ok(response)
     }
 
-    // Storage operations following boiler patterns
+    fn get_coupon_by_txid(&self, txid_lo: u128, txid_hi: u128) -> Result<CallResponse> {
+        let context = self.context()?;
+        let mut response = CallResponse::forward(&context.incoming_alkanes);
 
-    fn coupon_token_template_id(&self) -> Result<AlkaneId> {
-        let bytes = self.load("/coupon_token_template_id".as_bytes().to_vec());
+        let mut txid = [0u8; 32];
+        txid[0..16].copy_from_slice(&txid_lo.to_le_bytes());
+        txid[16..32].copy_from_slice(&txid_hi.to_le_bytes());
 
-        if bytes.len() < 32 {
-            return Err(anyhow!("Coupon token template ID not set"));
+        let coupons = self.coupons_by_txid(&txid)?;
+
+        // Format: [count (8 bytes)] + [AlkaneId_1 (32 bytes)] + ... in mint order
+        let mut data = Vec::with_capacity(8 + coupons.len() * 32);
+        data.extend_from_slice(&(coupons.len() as u64).to_le_bytes());
+        for coupon in coupons {
+            data.extend_from_slice(&coupon.block.to_le_bytes());
+            data.extend_from_slice(&coupon.tx.to_le_bytes());
         }
 
-        Ok(AlkaneId {
-            block: u128::from_le_bytes(bytes[0..16].try_into().map_err(|_| {
-                anyhow!("Failed to parse coupon template block ID from storage")
-            })?),
-            tx: u128::from_le_bytes(bytes[16..32].try_into().map_err(|_| {
-                anyhow!("Failed to parse coupon template tx ID from storage")
-            })?),
-        })
+        response.data = data;
+        Ok(response)
     }
 
-    fn set_coupon_token_template_id(&self, id: &AlkaneId) -> Result<()> {
-        let mut bytes = Vec::with_capacity(32);
-        bytes.extend_from_slice(&id.block.to_le_bytes());
-        bytes.extend_from_slice(&id.tx.to_le_bytes());
+    fn get_coupon_parent(&self, coupon_id: AlkaneId) -> Result<CallResponse> {
+        let context = self.context()?;
+        let mut response = CallResponse::forward(&context.incoming_alkanes);
 
-        self.store("/coupon_token_template_id".as_bytes().to_vec(), bytes);
-        Ok(())
+        let parent_id = storage::coupon_parent().get(self, &coupon_id)?.ok_or_else(|| {
+            anyhow!("Coupon {}:{} was not minted by a re-roll", coupon_id.block, coupon_id.tx)
+        })?;
+
+        response.data = Self::id_bytes(&parent_id);
+        Ok(response)
     }
 
-    fn success_threshold(&self) -> u8 {
-        let bytes = self.load("/success_threshold".as_bytes().to_vec());
-        if !bytes.is_empty() {
-            bytes[0]
-        } else {
-            144 // Default threshold
+    fn get_craft_inputs(&self, coupon_id: AlkaneId) -> Result<CallResponse> {
+        let context = self.context()?;
+        let mut response = CallResponse::forward(&context.incoming_alkanes);
+
+        let inputs = storage::craft_inputs(&coupon_id).all(self)?;
+
+        // Format: [count (8 bytes)] + [AlkaneId_1 (32 bytes)] + ... in crafting order
+        let mut data = Vec::with_capacity(8 + inputs.len() * 32);
+        data.extend_from_slice(&(inputs.len() as u64).to_le_bytes());
+        for input in inputs {
+            data.extend_from_slice(&Self::id_bytes(&input));
         }
-    }
 
-    fn set_success_threshold(&self, threshold: u8) {
-        self.store("/success_threshold".as_bytes().to_vec(), vec![threshold]);
+        response.data = data;
+        Ok(response)
     }
 
-    fn successful_coupons(&self) -> u128 {
-        self.load_u128("/successful_coupons")
+    fn get_txid_by_coupon(&self, coupon_id: AlkaneId) -> Result<CallResponse> {
+        let context = self.context()?;
+        let mut response = CallResponse::forward(&context.incoming_alkanes);
+
+        // 32 txid bytes in internal byte order
+        let txid = self.coupon_txid(&coupon_id)?.ok_or_else(|| {
+            anyhow!("No creation txid recorded for coupon {}:{}", coupon_id.block, coupon_id.tx)
+        })?;
+
+        response.data = txid.to_vec();
+        Ok(response)
     }
 
-    fn set_successful_coupons(&self, count: u128) {
-        self.store(
-            "/successful_coupons".as_bytes().to_vec(),
-            count.to_le_bytes().to_vec(),
-        );
+    fn get_coupon_status(&self, coupon_id: AlkaneId) -> Result<CallResponse> {
+        let context = self.context()?;
+        let mut response = CallResponse::forward(&context.incoming_alkanes);
+        let status = self.coupon_status(&coupon_id)?.unwrap_or(0);
+        response.data = (status as u128).to_le_bytes().to_vec();
+        Ok(response)
     }
 
-    fn failed_coupons(&self) -> u128 {
-        self.load_u128("/failed_coupons")
+    fn get_coupon_status_page(&self, offset: u128, limit: u128) -> Result<CallResponse> {
+        let context = self.context()?;
+        let mut response = CallResponse::forward(&context.incoming_alkanes);
+
+        let coupons = self.registered_coupons_page(offset, limit)?;
+
+        // Format: [total registered (16)] + [count (8 bytes)] + [AlkaneId (32) + status (16)] per coupon
+        let mut data = Vec::with_capacity(24 + coupons.len() * 48);
+        data.extend_from_slice(&self.registered_coupons_count()?.to_le_bytes());
+        data.extend_from_slice(&(coupons.len() as u64).to_le_bytes());
+        for coupon in coupons {
+            let status = self.coupon_status(&coupon)?.unwrap_or(0);
+            data.extend_from_slice(&Self::id_bytes(&coupon));
+            data.extend_from_slice(&(status as u128).to_le_bytes());
+        }
+
+        response.data = data;
+        Ok(response)
     }
 
-    fn set_failed_coupons(&self, count: u128) {
-        self.store(
-            "/failed_coupons".as_bytes().to_vec(),
-            count.to_le_bytes().to_vec(),
-        );
+    fn is_coupon_redeemed(&self, coupon_id: AlkaneId) -> Result<CallResponse> {
+        let context = self.context()?;
+        let mut response = CallResponse::forward(&context.incoming_alkanes);
+        let is_redeemed = self.is_coupon_redeemed_internal(&coupon_id)?;
+        response.data = (if is_redeemed { 1u128 } else { 0u128 }).to_le_bytes().to_vec();
+        Ok(response)
     }
 
-    fn total_coupons(&self) -> u128 {
-        self.successful_coupons().saturating_add(self.failed_coupons())
+    fn get_factory_info(&self) -> Result<CallResponse> {
+        let context = self.context()?;
+        let mut response = CallResponse::forward(&context.incoming_alkanes);
+        
+        let template_id = self.coupon_token_template_id()?;
+        
+        // Format: [template_id (32)] + [success_threshold (1)] + [successful_coupons (16)] + [failed_coupons (16)]
+        // Total: 65 bytes
+        let mut data = Vec::with_capacity(65);
+        
+        // Template ID (32 bytes)
+        data.extend_from_slice(&template_id.block.to_le_bytes());
+        data.extend_from_slice(&template_id.tx.to_le_bytes());
+        
+        // Configuration values
+        data.push(self.effective_config()?.success_threshold); // 1 byte
+        
+        // Statistics
+        data.extend_from_slice(&self.successful_coupons()?.to_le_bytes()); // 16 bytes
+        data.extend_from_slice(&self.failed_coupons()?.to_le_bytes());     // 16 bytes
+        
+        response.data = data;
+        Ok(response)
     }
 
-    // Registry operations following boiler patterns
+    fn get_pause_status(&self) -> Result<CallResponse> {
+        let context = self.context()?;
+        let mut response = CallResponse::forward(&context.incoming_alkanes);
+
+        // Format: [paused (16)] + [reason (UTF-8, remaining bytes)]
+        let mut data = Vec::new();
+        data.extend_from_slice(&(if self.is_paused()? { 1u128 } else { 0u128 }).to_le_bytes());
+        data.extend_from_slice(self.pause_reason()?.as_bytes());
 
-    fn is_registered_coupon_internal(&self, coupon_id: &AlkaneId) -> bool {
-        let key = format!("/registered_coupons/{}_{}", coupon_id.block, coupon_id.tx).into_bytes();
-        let bytes = self.load(key);
-        !bytes.is_empty() && bytes[0] == 1
+        response.data = data;
+        Ok(response)
     }
 
-    fn register_coupon(&self, coupon_id: &AlkaneId) {
-        // Store individual registration for O(1) lookup
-        let key = format!("/registered_coupons/{}_{}", coupon_id.block, coupon_id.tx).into_bytes();
-        self.store(key, vec![1u8]);
+    fn get_circuit_breaker(&self) -> Result<CallResponse> {
+        let context = self.context()?;
+        let mut response = CallResponse::forward(&context.incoming_alkanes);
 
-        // Add to centralized list for enumeration
-        let mut coupons_list = self.registered_coupons_list();
-        coupons_list.push(coupon_id.clone());
-        self.set_registered_coupons_list(coupons_list);
+        // Format: [window (16)] + [max_win_ratio_bps (16)] + [recent_wins (16)] + [filled (16)]
+        let mut data = Vec::with_capacity(64);
+        for cell in [
+            storage::breaker_window(),
+            storage::breaker_max_win_ratio_bps(),
+            storage::breaker_recent_wins(),
+            storage::breaker_filled(),
+        ] {
+            data.extend_from_slice(&cell.get_or_default(self)?.to_le_bytes());
+        }
 
-        // Update count
-        let new_count = self.registered_coupons_count().checked_add(1).unwrap_or(0);
-        self.set_registered_coupons_count(new_count);
+        response.data = data;
+        Ok(response)
     }
 
-    fn registered_coupons_list(&self) -> Vec<AlkaneId> {
-        let bytes = self.load("/registered_coupons_list".as_bytes().to_vec());
-        if bytes.is_empty() {
-            return Vec::new();
-        }
+    fn get_config(&self) -> Result<CallResponse> {
+        let context = self.context()?;
+        let mut response = CallResponse::forward(&context.incoming_alkanes);
+        let config = self.effective_config()?;
 
-        let mut coupons = Vec::new();
-        let mut offset = 0;
+        let mut entries = Vec::new();
 
-        // Each AlkaneId is 32 bytes (16 bytes block + 16 bytes tx)
-        while offset + 32 <= bytes.len() {
-            let block_bytes: [u8; 16] = bytes[offset..offset+16].try_into().unwrap_or([0; 16]);
-            let tx_bytes: [u8; 16] = bytes[offset+16..offset+32].try_into().unwrap_or([0; 16]);
-            
-            coupons.push(AlkaneId {
-                block: u128::from_le_bytes(block_bytes),
-                tx: u128::from_le_bytes(tx_bytes),
-            });
-            
-            offset += 32;
+        let template_id = self.coupon_token_template_id()?;
+        Self::push_config_entry(&mut entries, CONFIG_TAG_COUPON_TEMPLATE, &Self::id_bytes(&template_id));
+
+        // One entry per tier with a dedicated template: [tier (1)] + [template (32)]
+        for tier in OutcomeTier::ALL.map(OutcomeTier::index) {
+            if let Some(template) = storage::tier_templates().get(self, &tier)? {
+                let mut value = vec![tier as u8];
+                value.extend_from_slice(&Self::id_bytes(&template));
+                Self::push_config_entry(&mut entries, CONFIG_TAG_TIER_TEMPLATE, &value);
+            }
         }
 
-        coupons
-    }
+        Self::push_config_entry(
+            &mut entries,
+            CONFIG_TAG_SUCCESS_THRESHOLD,
+            &(config.success_threshold as u128).to_le_bytes(),
+        );
 
-    fn set_registered_coupons_list(&self, coupons: Vec<AlkaneId>) {
-        let mut bytes = Vec::new();
-        
-        for coupon in coupons {
-            bytes.extend_from_slice(&coupon.block.to_le_bytes());
-            bytes.extend_from_slice(&coupon.tx.to_le_bytes());
-        }
-        
-        self.store("/registered_coupons_list".as_bytes().to_vec(), bytes);
-    }
+        let mut stake_limits = config.minimum_stake.to_le_bytes().to_vec();
+        stake_limits.extend_from_slice(&config.maximum_stake.to_le_bytes());
+        Self::push_config_entry(&mut entries, CONFIG_TAG_STAKE_LIMITS, &stake_limits);
 
-    fn registered_coupons_count(&self) -> u128 {
-        self.load_u128("/registered_coupons_count")
-    }
+        // [minimum_stake (16) + bonus (1)] per step, empty for the default linear bonus
+        Self::push_config_entry(
+            &mut entries,
+            CONFIG_TAG_BONUS_TABLE,
+            &Self::bonus_table_bytes(&config.bonus_table),
+        );
+
+        Self::push_config_entry(&mut entries, CONFIG_TAG_FEE, &config.fee_bps.to_le_bytes());
 
-    fn set_registered_coupons_count(&self, count: u128) {
-        self.store(
-            "/registered_coupons_count".as_bytes().to_vec(),
-            count.to_le_bytes().to_vec(),
+        // [paused (1)] + [reason (UTF-8)]
+        let mut pause_state = vec![if self.is_paused()? { 1u8 } else { 0u8 }];
+        pause_state.extend_from_slice(self.pause_reason()?.as_bytes());
+        Self::push_config_entry(&mut entries, CONFIG_TAG_PAUSE_STATE, &pause_state);
+
+        Self::push_config_entry(
+            &mut entries,
+            CONFIG_TAG_RANDOMNESS_MODE,
+            &[RANDOMNESS_MODE_TXID_MERKLE],
         );
-    }
 
-    // Helper function to load u128 values from storage
-    fn load_u128(&self, key_str: &str) -> u128 {
-        let key = key_str.as_bytes().to_vec();
-        let bytes = self.load(key);
-        if bytes.len() >= 16 {
-            let bytes_array: [u8; 16] = bytes[0..16].try_into().unwrap_or([0; 16]);
-            u128::from_le_bytes(bytes_array)
-        } else {
-            0
+        // [payout_token (32)] + [ratio_numerator (16)] + [ratio_denominator (16)]
+        Self::push_config_entry(&mut entries, CONFIG_TAG_PAYOUT, &Self::payout_bytes(&config));
+
+        Self::push_config_entry(&mut entries, CONFIG_TAG_TIMELOCK, &config.timelock.to_le_bytes());
+
+        let mut circuit_breaker = storage::breaker_window().get_or_default(self)?.to_le_bytes().to_vec();
+        circuit_breaker.extend_from_slice(&storage::breaker_max_win_ratio_bps().get_or_default(self)?.to_le_bytes());
+        Self::push_config_entry(&mut entries, CONFIG_TAG_CIRCUIT_BREAKER, &circuit_breaker);
+
+        Self::push_config_entry(
+            &mut entries,
+            CONFIG_TAG_SCHEMA_VERSION,
+            &migration::schema_version(self)?.to_le_bytes(),
+        );
+
+        // One entry per change still waiting for its activation block
+        for (change_id, change) in self.scheduled_changes()? {
+            Self::push_config_entry(
+                &mut entries,
+                CONFIG_TAG_PENDING_CHANGE,
+                &Self::pending_change_bytes(change_id, &change),
+            );
         }
+
+        Self::push_config_entry(
+            &mut entries,
+            CONFIG_TAG_TIER_BOUNDARIES,
+            &config.tier_boundaries.encode(),
+        );
+
+        Self::push_config_entry(&mut entries, CONFIG_TAG_REROLL_BONUS, &[config.reroll_bonus]);
+
+        if let Some(template) = storage::crafting_template_id().get(self)? {
+            let value = Self::id_bytes(&template);
+            Self::push_config_entry(&mut entries, CONFIG_TAG_CRAFTING_TEMPLATE, &value);
+        }
+
+        // Format: [length (u32 LE) of what follows] + [version (1)] + entries
+        let mut data = Vec::with_capacity(5 + entries.len());
+        data.extend_from_slice(&((entries.len() + 1) as u32).to_le_bytes());
+        data.push(CONFIG_VERSION);
+        data.extend_from_slice(&entries);
+
+        response.data = data;
+        Ok(response)
     }
 
-    // Getter functions for frontend/external consumption
+    fn calculate_base_xor(&self) -> Result<CallResponse> {
+        let context = self.context()?;
+        let mut response = CallResponse::forward(&context.incoming_alkanes);
+        let base_xor = self.calculate_base_xor_internal()?;
+        response.data = (base_xor as u128).to_le_bytes().to_vec();
+        Ok(response)
+    }
 
-    fn get_successful_coupons(&self) -> Result<CallResponse> {
+    fn get_minimum_stake(&self) -> Result<CallResponse> {
         let context = self.context()?;
         let mut response = CallResponse::forward(&context.incoming_alkanes);
-        response.data = self.successful_coupons().to_le_bytes().to_vec();
+        response.data = self.effective_config()?.minimum_stake.to_le_bytes().to_vec();
         Ok(response)
     }
 
-    fn get_failed_coupons(&self) -> Result<CallResponse> {
+    fn get_stake_limits(&self) -> Result<CallResponse> {
         let context = self.context()?;
         let mut response = CallResponse::forward(&context.incoming_alkanes);
-        response.data = self.failed_coupons().to_le_bytes().to_vec();
+
+        // Format: [minimum_stake (16)] + [maximum_stake (16)], a zero maximum means unlimited
+        let config = self.effective_config()?;
+        let mut data = Vec::with_capacity(32);
+        data.extend_from_slice(&config.minimum_stake.to_le_bytes());
+        data.extend_from_slice(&config.maximum_stake.to_le_bytes());
+
+        response.data = data;
         Ok(response)
     }
 
-    fn get_total_coupons(&self) -> Result<CallResponse> {
+    fn get_bonus_table(&self) -> Result<CallResponse> {
         let context = self.context()?;
         let mut response = CallResponse::forward(&context.incoming_alkanes);
-        response.data = self.total_coupons().to_le_bytes().to_vec();
+
+        // Format: [count (8 bytes)] + [minimum_stake (16) + bonus (16)] per step
+        // An empty table means the default of 1 bonus point per 1000 staked
+        let bonus_table = self.effective_config()?.bonus_table;
+        let mut data = Vec::with_capacity(8 + bonus_table.len() * 32);
+        data.extend_from_slice(&(bonus_table.len() as u64).to_le_bytes());
+        for (minimum_stake, bonus) in bonus_table {
+            data.extend_from_slice(&minimum_stake.to_le_bytes());
+            data.extend_from_slice(&(bonus as u128).to_le_bytes());
+        }
+
+        response.data = data;
         Ok(response)
     }
 
-    fn get_success_threshold(&self) -> Result<CallResponse> {
+    fn get_fee(&self) -> Result<CallResponse> {
         let context = self.context()?;
         let mut response = CallResponse::forward(&context.incoming_alkanes);
-        response.data = (self.success_threshold() as u128).to_le_bytes().to_vec();
+        response.data = self.effective_config()?.fee_bps.to_le_bytes().to_vec();
         Ok(response)
     }
 
-    fn get_coupon_token_template_id(&self) -> Result<CallResponse> {
+    fn get_timelock(&self) -> Result<CallResponse> {
         let context = self.context()?;
         let mut response = CallResponse::forward(&context.incoming_alkanes);
-        let template_id = self.coupon_token_template_id()?;
-        
-        let mut data = Vec::with_capacity(32);
-        data.extend_from_slice(&template_id.block.to_le_bytes());
-        data.extend_from_slice(&template_id.tx.to_le_bytes());
-        
-        response.data = data;
+        response.data = self.effective_config()?.timelock.to_le_bytes().to_vec();
         Ok(response)
     }
 
-    fn get_all_registered_coupons(&self) -> Result<CallResponse> {
+    fn get_pending_changes(&self) -> Result<CallResponse> {
         let context = self.context()?;
         let mut response = CallResponse::forward(&context.incoming_alkanes);
 
-        let coupons_list = self.registered_coupons_list();
-        let coupons_count = coupons_list.len();
+        // Due changes already read as applied, so only later ones are listed
+        let scheduled = self.scheduled_changes()?;
 
-        // Format: [count (8 bytes)] + [AlkaneId_1 (32 bytes)] + [AlkaneId_2 (32 bytes)] + ...
+        // Format: [count (8 bytes)] + per change:
+        // [change_id (16)] + [kind (16)] + [activation_block (16)] + [param_count (16)] + [params (16 each)]
         let mut data = Vec::new();
-
-        // Add count of registered coupons (as u64 for compatibility)
-        data.extend_from_slice(&(coupons_count as u64).to_le_bytes());
-
-        // Add each registered coupon AlkaneId
-        for coupon in coupons_list {
-            data.extend_from_slice(&coupon.block.to_le_bytes()); // 16 bytes
-            data.extend_from_slice(&coupon.tx.to_le_bytes());    // 16 bytes
+        data.extend_from_slice(&(scheduled.len() as u64).to_le_bytes());
+        for (change_id, change) in scheduled {
+            data.extend_from_slice(&Self::pending_change_bytes(change_id, &change));
         }
 
         response.data = data;
         Ok(response)
     }
 
-    fn is_registered_coupon(&self, coupon_id: AlkaneId) -> Result<CallResponse> {
+    /// [change_id (16)] + [kind (16)] + [activation_block (16)] + [param_count (16)] + [params (16 each)]
+    fn pending_change_bytes(change_id: u128, change: &PendingChange) -> Vec<u8> {
+        let mut data = Vec::with_capacity(64 + change.params.len() * 16);
+        data.extend_from_slice(&change_id.to_le_bytes());
+        data.extend_from_slice(&change.kind.to_le_bytes());
+        data.extend_from_slice(&change.activation_block.to_le_bytes());
+        data.extend_from_slice(&(change.params.len() as u128).to_le_bytes());
+        for param in &change.params {
+            data.extend_from_slice(&param.to_le_bytes());
+        }
+        data
+    }
+
+    fn get_treasury_balance(&self, token: AlkaneId) -> Result<CallResponse> {
         let context = self.context()?;
         let mut response = CallResponse::forward(&context.incoming_alkanes);
-        let is_registered = self.is_registered_coupon_internal(&coupon_id);
-        response.data = (if is_registered { 1u128 } else { 0u128 }).to_le_bytes().to_vec();
+        response.data = self.treasury_balance(&token)?.to_le_bytes().to_vec();
         Ok(response)
     }
 
-    fn get_factory_info(&self) -> Result<CallResponse> {
+    fn get_prize_inventory(&self, token: AlkaneId) -> Result<CallResponse> {
         let context = self.context()?;
         let mut response = CallResponse::forward(&context.incoming_alkanes);
-        
-        let template_id = self.coupon_token_template_id()?;
-        
-        // Format: [template_id (32)] + [success_threshold (1)] + [successful_coupons (16)] + [failed_coupons (16)]
-        // Total: 65 bytes
-        let mut data = Vec::with_capacity(65);
-        
-        // Template ID (32 bytes)
-        data.extend_from_slice(&template_id.block.to_le_bytes());
-        data.extend_from_slice(&template_id.tx.to_le_bytes());
-        
-        // Configuration values
-        data.push(self.success_threshold()); // 1 byte
-        
-        // Statistics
-        data.extend_from_slice(&self.successful_coupons().to_le_bytes()); // 16 bytes
-        data.extend_from_slice(&self.failed_coupons().to_le_bytes());     // 16 bytes
-        
-        response.data = data;
+        response.data = self.prize_inventory(&token)?.to_le_bytes().to_vec();
         Ok(response)
     }
 
-    fn calculate_base_xor(&self) -> Result<CallResponse> {
+    fn get_tier_prizes(&self, tier: u128) -> Result<CallResponse> {
         let context = self.context()?;
         let mut response = CallResponse::forward(&context.incoming_alkanes);
-        let base_xor = self.calculate_base_xor_internal()?;
-        response.data = (base_xor as u128).to_le_bytes().to_vec();
+        let tier = Self::parse_prize_tier(tier)?.index();
+
+        let prizes = self.tier_prizes(tier)?;
+
+        // Format: [count (8 bytes)] + [AlkaneId_1 (32 bytes)] + ... in claim order
+        let mut data = Vec::with_capacity(8 + prizes.len() * 32);
+        data.extend_from_slice(&(prizes.len() as u64).to_le_bytes());
+        for prize in prizes {
+            data.extend_from_slice(&prize.block.to_le_bytes());
+            data.extend_from_slice(&prize.tx.to_le_bytes());
+        }
+
+        response.data = data;
         Ok(response)
     }
 
-    fn get_minimum_stake(&self) -> Result<CallResponse> {
+    fn get_payout_config(&self) -> Result<CallResponse> {
         let context = self.context()?;
         let mut response = CallResponse::forward(&context.incoming_alkanes);
-        response.data = MINIMUM_STAKE_AMOUNT.to_le_bytes().to_vec();
+
+        // Format: [payout_token (32)] + [ratio_numerator (16)] + [ratio_denominator (16)]
+        // An all-zero payout token means winners are paid 1:1 in their stake token
+        response.data = Self::payout_bytes(&self.effective_config()?);
         Ok(response)
     }
+
+    fn payout_bytes(config: &EffectiveConfig) -> Vec<u8> {
+        let (payout_token, ratio_numerator, ratio_denominator) = config
+            .payout
+            .clone()
+            .unwrap_or((AlkaneId { block: 0, tx: 0 }, 0, 0));
+        let mut data = Self::id_bytes(&payout_token);
+        data.extend_from_slice(&ratio_numerator.to_le_bytes());
+        data.extend_from_slice(&ratio_denominator.to_le_bytes());
+        data
+    }
+}
+
+impl Store for CouponFactory {
+    fn load_raw(&self, key: &str) -> Vec<u8> {
+        self.load(key.as_bytes().to_vec())
+    }
+
+    fn store_raw(&self, key: &str, value: Vec<u8>) {
+        self.store(key.as_bytes().to_vec(), value);
+    }
 }
 
 declare_alkane! {
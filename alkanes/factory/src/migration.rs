@@ -0,0 +1,268 @@
+//! Migration steps describe historical layouts, so they work on raw keys
+//! instead of the factory's live storage cells, which may change over time.
+
+use anyhow::{anyhow, Result};
+use gamba_storage::{Cell, Store};
+
+/// Storage layout version written by the current factory code
+pub const CURRENT_SCHEMA_VERSION: u128 = 1;
+
+/// Registered coupons moved out of the legacy registry list per `Migrate` call
+pub const MIGRATION_BATCH_SIZE: u128 = 100;
+
+/// Determine the layout version of the stored state.
+///
+/// - 0: the legacy layout, without a `/schema_version` key. Registered coupons
+///   are packed into one `/registered_coupons_list` blob of 32-byte ids, and a
+///   single admin token may sit under `/auth_token`
+/// - 1: per-role auth tokens under `/roles/{role}/...` and the coupon registry
+///   indexed under `/registered_coupons/index/{n}`
+pub fn schema_version<S: Store + ?Sized>(storage: &S) -> Result<u128> {
+    Ok(Cell::<u128>::new("/schema_version").get(storage)?.unwrap_or(0))
+}
+
+pub fn set_schema_version<S: Store + ?Sized>(storage: &S, version: u128) {
+    Cell::<u128>::new("/schema_version").set(storage, &version);
+}
+
+/// Upgrade a legacy layout to the current one. Returns the number of registered
+/// coupons still waiting to move, so a large registry takes several calls; the
+/// schema version is only written once the whole registry has moved.
+pub fn migrate<S: Store + ?Sized>(storage: &S) -> Result<u128> {
+    let version = schema_version(storage)?;
+    if version > CURRENT_SCHEMA_VERSION {
+        return Err(anyhow!(
+            "Schema version {} is newer than supported version {}",
+            version,
+            CURRENT_SCHEMA_VERSION
+        ));
+    }
+    if version == CURRENT_SCHEMA_VERSION {
+        return Ok(0);
+    }
+
+    // A deployed but uninitialized factory gets its layout from Initialize
+    if storage.load_raw("/coupon_token_template_id").len() < 32 {
+        return Err(anyhow!("Factory is not initialized, there is nothing to migrate"));
+    }
+
+    migrate_auth_token(storage)?;
+    let remaining = migrate_registry_batch(storage)?;
+    if remaining == 0 {
+        set_schema_version(storage, CURRENT_SCHEMA_VERSION);
+    }
+    Ok(remaining)
+}
+
+/// The legacy auth token becomes the admin token and nothing else; the admin
+/// grants the other roles explicitly. A legacy factory without one never had
+/// an admin and gets none.
+fn migrate_auth_token<S: Store + ?Sized>(storage: &S) -> Result<()> {
+    let auth_token = storage.load_raw("/auth_token");
+    if auth_token.is_empty() {
+        return Ok(());
+    }
+    if auth_token.len() != 32 {
+        return Err(anyhow!("Legacy auth token has {} bytes, expected 32", auth_token.len()));
+    }
+
+    // Role 0 is the admin
+    register_role_token(storage, 0, &auth_token)?;
+    storage.store_raw("/auth_token", Vec::new());
+    Ok(())
+}
+
+fn register_role_token<S: Store + ?Sized>(storage: &S, role: u128, token: &[u8]) -> Result<()> {
+    let block = u128::from_le_bytes(token[0..16].try_into()?);
+    let tx = u128::from_le_bytes(token[16..32].try_into()?);
+    storage.store_raw(&format!("/roles/{}/{}_{}", role, block, tx), vec![1u8]);
+
+    let mut tokens = storage.load_raw(&format!("/roles/{}/tokens", role));
+    tokens.extend_from_slice(token);
+    storage.store_raw(&format!("/roles/{}/tokens", role), tokens);
+    Ok(())
+}
+
+/// Move the next batch of the legacy `/registered_coupons_list` blob to one key
+/// per index with a reverse lookup from id to index. `/migration/registry_cursor`
+/// remembers how far earlier calls got. Returns the number of coupons left.
+fn migrate_registry_batch<S: Store + ?Sized>(storage: &S) -> Result<u128> {
+    let blob = storage.load_raw("/registered_coupons_list");
+    if blob.len() % 32 != 0 {
+        return Err(anyhow!(
+            "Registered coupons list has {} bytes, expected a multiple of 32",
+            blob.len()
+        ));
+    }
+
+    let total = (blob.len() / 32) as u128;
+    let cursor = Cell::<u128>::new("/migration/registry_cursor");
+    let start = cursor.get(storage)?.unwrap_or(0);
+    let end = total.min(start.saturating_add(MIGRATION_BATCH_SIZE));
+
+    for index in start..end {
+        let offset = index as usize * 32;
+        let coupon = &blob[offset..offset + 32];
+        let block = u128::from_le_bytes(coupon[0..16].try_into()?);
+        let tx = u128::from_le_bytes(coupon[16..32].try_into()?);
+
+        storage.store_raw(&format!("/registered_coupons/index/{}", index), coupon.to_vec());
+        storage.store_raw(
+            &format!("/registered_coupons/index_of/{}_{}", block, tx),
+            index.to_le_bytes().to_vec(),
+        );
+    }
+
+    if end < total {
+        cursor.set(storage, &end);
+        return Ok(total - end);
+    }
+
+    storage.store_raw("/registered_coupons_count", total.to_le_bytes().to_vec());
+    storage.store_raw("/registered_coupons_list", Vec::new());
+    cursor.clear(storage);
+    Ok(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gamba_storage::MemoryStore;
+
+    fn id_bytes(block: u128, tx: u128) -> Vec<u8> {
+        let mut bytes = block.to_le_bytes().to_vec();
+        bytes.extend_from_slice(&tx.to_le_bytes());
+        bytes
+    }
+
+    /// Keys written by an initialized legacy factory with `coupons` registered
+    fn legacy_factory(coupons: u128) -> MemoryStore {
+        let storage = MemoryStore::default();
+        storage.store_raw("/success_threshold", vec![144]);
+        storage.store_raw("/coupon_token_template_id", id_bytes(4, 0x601));
+        storage.store_raw("/successful_coupons", 1u128.to_le_bytes().to_vec());
+        storage.store_raw("/failed_coupons", (coupons - 1).to_le_bytes().to_vec());
+
+        let mut blob = Vec::new();
+        for tx in 10..10 + coupons {
+            blob.extend_from_slice(&id_bytes(2, tx));
+            storage.store_raw(&format!("/registered_coupons/2_{}", tx), vec![1u8]);
+        }
+        storage.store_raw("/registered_coupons_list", blob);
+        storage.store_raw("/registered_coupons_count", coupons.to_le_bytes().to_vec());
+        storage
+    }
+
+    #[test]
+    fn test_migrate_legacy_layout_to_current() {
+        let storage = legacy_factory(2);
+        storage.store_raw("/auth_token", id_bytes(2, 7));
+
+        assert_eq!(schema_version(&storage).unwrap(), 0);
+        assert_eq!(migrate(&storage).unwrap(), 0);
+        assert_eq!(schema_version(&storage).unwrap(), CURRENT_SCHEMA_VERSION);
+
+        // The legacy auth token is the admin and holds no other role
+        assert_eq!(storage.load_raw("/roles/0/2_7"), vec![1u8]);
+        assert_eq!(storage.load_raw("/roles/0/tokens"), id_bytes(2, 7));
+        for role in 1u128..4 {
+            assert!(storage.load_raw(&format!("/roles/{}/2_7", role)).is_empty());
+            assert!(storage.load_raw(&format!("/roles/{}/tokens", role)).is_empty());
+        }
+        assert!(storage.load_raw("/auth_token").is_empty());
+
+        assert_eq!(storage.load_raw("/registered_coupons/index/0"), id_bytes(2, 10));
+        assert_eq!(storage.load_raw("/registered_coupons/index/1"), id_bytes(2, 11));
+        assert_eq!(
+            storage.load_raw("/registered_coupons/index_of/2_11"),
+            1u128.to_le_bytes().to_vec()
+        );
+        assert_eq!(
+            storage.load_raw("/registered_coupons_count"),
+            2u128.to_le_bytes().to_vec()
+        );
+        assert!(storage.load_raw("/registered_coupons_list").is_empty());
+
+        // Registry flags already read as active, and unrelated keys are left alone
+        assert_eq!(storage.load_raw("/registered_coupons/2_10"), vec![1u8]);
+        assert_eq!(storage.load_raw("/registered_coupons/2_11"), vec![1u8]);
+        assert_eq!(storage.load_raw("/success_threshold"), vec![144]);
+        assert_eq!(storage.load_raw("/successful_coupons"), 1u128.to_le_bytes().to_vec());
+        assert_eq!(storage.load_raw("/failed_coupons"), 1u128.to_le_bytes().to_vec());
+
+        // A second run is a no-op
+        assert_eq!(migrate(&storage).unwrap(), 0);
+        assert_eq!(storage.load_raw("/roles/0/tokens"), id_bytes(2, 7));
+    }
+
+    #[test]
+    fn test_migrate_legacy_layout_without_auth_token_grants_no_role() {
+        let storage = legacy_factory(1);
+
+        assert_eq!(migrate(&storage).unwrap(), 0);
+        assert_eq!(schema_version(&storage).unwrap(), CURRENT_SCHEMA_VERSION);
+        for role in 0u128..4 {
+            assert!(storage.load_raw(&format!("/roles/{}/tokens", role)).is_empty());
+        }
+        assert_eq!(storage.load_raw("/registered_coupons/index/0"), id_bytes(2, 10));
+    }
+
+    #[test]
+    fn test_migrate_refuses_uninitialized_factory() {
+        let storage = MemoryStore::default();
+
+        assert!(migrate(&storage).is_err());
+        assert_eq!(schema_version(&storage).unwrap(), 0);
+        assert!(storage.is_empty());
+    }
+
+    #[test]
+    fn test_migrate_moves_large_registry_in_batches() {
+        let coupons = MIGRATION_BATCH_SIZE * 2 + 5;
+        let storage = legacy_factory(coupons);
+
+        assert_eq!(migrate(&storage).unwrap(), MIGRATION_BATCH_SIZE + 5);
+        assert_eq!(schema_version(&storage).unwrap(), 0);
+        assert_eq!(migrate(&storage).unwrap(), 5);
+        assert_eq!(migrate(&storage).unwrap(), 0);
+        assert_eq!(schema_version(&storage).unwrap(), CURRENT_SCHEMA_VERSION);
+
+        let last = coupons - 1;
+        assert_eq!(
+            storage.load_raw(&format!("/registered_coupons/index/{}", last)),
+            id_bytes(2, 10 + last)
+        );
+        assert_eq!(
+            storage.load_raw("/registered_coupons_count"),
+            coupons.to_le_bytes().to_vec()
+        );
+        assert!(storage.load_raw("/registered_coupons_list").is_empty());
+        assert!(storage.load_raw("/migration/registry_cursor").is_empty());
+    }
+
+    #[test]
+    fn test_migrate_rejects_malformed_registry_blob() {
+        let storage = legacy_factory(1);
+        storage.store_raw("/registered_coupons_list", vec![0u8; 40]);
+
+        assert!(migrate(&storage).is_err());
+        assert_eq!(schema_version(&storage).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_migrate_current_version_is_noop() {
+        let storage = MemoryStore::default();
+        set_schema_version(&storage, CURRENT_SCHEMA_VERSION);
+
+        assert_eq!(migrate(&storage).unwrap(), 0);
+        assert_eq!(storage.len(), 1);
+    }
+
+    #[test]
+    fn test_migrate_rejects_newer_version() {
+        let storage = MemoryStore::default();
+        set_schema_version(&storage, CURRENT_SCHEMA_VERSION + 1);
+
+        assert!(migrate(&storage).is_err());
+    }
+}
@@ -0,0 +1,275 @@
+//! Storage layout of the coupon factory.
+//!
+//! Every key the factory reads or writes is declared here as a typed cell,
+//! list or map, so the byte layout lives in one place. Migrations describe
+//! older layouts and keep their own raw keys.
+
+use crate::leaderboard::Leaderboard;
+use crate::{PendingChange, PeriodStats};
+use alkanes_support::id::AlkaneId;
+use gamba_outcome::TierBoundaries;
+use gamba_storage::{Cell, IndexedList, Map, MapKey};
+
+// Configuration
+
+pub fn success_threshold() -> Cell<u8> {
+    Cell::new("/success_threshold")
+}
+
+pub fn coupon_token_template_id() -> Cell<AlkaneId> {
+    Cell::new("/coupon_token_template_id")
+}
+
+pub fn tier_templates() -> Map<u128, AlkaneId> {
+    Map::new("/tier_templates")
+}
+
+pub fn tier_boundaries() -> Cell<TierBoundaries> {
+    Cell::new("/tier_boundaries")
+}
+
+pub fn minimum_stake() -> Cell<u128> {
+    Cell::new("/minimum_stake")
+}
+
+pub fn maximum_stake() -> Cell<u128> {
+    Cell::new("/maximum_stake")
+}
+
+/// Steps of 17 bytes: [minimum_stake (16)] + [bonus (1)]
+pub fn bonus_table() -> Cell<Vec<u8>> {
+    Cell::new("/bonus_table")
+}
+
+pub fn fee_bps() -> Cell<u128> {
+    Cell::new("/fee_bps")
+}
+
+pub fn timelock() -> Cell<u128> {
+    Cell::new("/timelock")
+}
+
+pub fn payout_token() -> Cell<AlkaneId> {
+    Cell::new("/payout_token")
+}
+
+pub fn payout_ratio_numerator() -> Cell<u128> {
+    Cell::new("/payout_ratio_numerator")
+}
+
+pub fn payout_ratio_denominator() -> Cell<u128> {
+    Cell::new("/payout_ratio_denominator")
+}
+
+pub fn reroll_bonus() -> Cell<u8> {
+    Cell::new("/reroll_bonus")
+}
+
+pub fn crafting_template_id() -> Cell<AlkaneId> {
+    Cell::new("/crafting_template_id")
+}
+
+// Roles: a flag per (role, token) for O(1) checks plus a packed list for enumeration
+
+pub fn role_flags(role: u128) -> Map<AlkaneId, bool> {
+    Map::new(format!("/roles/{}", role))
+}
+
+pub fn role_tokens(role: u128) -> Cell<Vec<AlkaneId>> {
+    Cell::new(format!("/roles/{}/tokens", role))
+}
+
+/// The single admin token of a legacy factory, replaced by roles on migration
+pub fn legacy_auth_token() -> Cell<AlkaneId> {
+    Cell::new("/auth_token")
+}
+
+// Timelocked configuration changes
+
+pub fn pending_changes() -> IndexedList<PendingChange> {
+    IndexedList::under("/pending_changes")
+}
+
+pub fn first_open_change() -> Cell<u128> {
+    Cell::new("/pending_changes/first_open")
+}
+
+// Counters and statistics
+
+pub fn successful_coupons() -> Cell<u128> {
+    Cell::new("/successful_coupons")
+}
+
+pub fn failed_coupons() -> Cell<u128> {
+    Cell::new("/failed_coupons")
+}
+
+pub fn crafted_coupons() -> Cell<u128> {
+    Cell::new("/crafted_coupons")
+}
+
+pub fn block_stats() -> Map<u128, PeriodStats> {
+    Map::new("/stats/block")
+}
+
+pub fn epoch_stats() -> Map<u128, PeriodStats> {
+    Map::new("/stats/epoch")
+}
+
+pub fn epoch_length() -> Cell<u128> {
+    Cell::new("/epoch/length")
+}
+
+pub fn epoch_anchor_epoch() -> Cell<u128> {
+    Cell::new("/epoch/anchor_epoch")
+}
+
+pub fn epoch_anchor_height() -> Cell<u128> {
+    Cell::new("/epoch/anchor_height")
+}
+
+pub fn epoch_previous_length() -> Cell<u128> {
+    Cell::new("/epoch/previous_length")
+}
+
+/// Top rolls of all time
+pub fn all_time_leaderboard() -> Cell<Leaderboard> {
+    Cell::new("/leaderboard/all_time")
+}
+
+/// Top rolls per epoch; only the current epoch is served
+pub fn epoch_leaderboards() -> Map<u128, Leaderboard> {
+    Map::new("/leaderboard/epoch")
+}
+
+/// Cumulative per-token volume: `staked`, `burned`, `treasury` and `paid_out`
+pub fn volume(field: &str) -> Map<AlkaneId, u128> {
+    Map::with_suffix("/volume", field)
+}
+
+pub fn volume_listed() -> Map<AlkaneId, bool> {
+    Map::with_suffix("/volume", "listed")
+}
+
+pub fn staked_tokens() -> IndexedList<AlkaneId> {
+    IndexedList::under("/volume/tokens")
+}
+
+// Treasury and prizes
+
+pub fn treasury_balances() -> Map<AlkaneId, u128> {
+    Map::new("/treasury")
+}
+
+pub fn prize_inventory() -> Map<AlkaneId, u128> {
+    Map::new("/prize_inventory")
+}
+
+/// Tier prize vault: a FIFO queue of NFT ids per tier between the head and tail cursors
+pub fn prize_vault_head(tier: u128) -> Cell<u128> {
+    Cell::new(format!("/prize_vault/{}/head", tier))
+}
+
+pub fn prize_vault_tail(tier: u128) -> Cell<u128> {
+    Cell::new(format!("/prize_vault/{}/tail", tier))
+}
+
+pub fn prize_vault_slots(tier: u128) -> Map<u128, AlkaneId> {
+    Map::new(format!("/prize_vault/{}", tier))
+}
+
+// Pause state and circuit breaker
+
+pub fn paused() -> Cell<bool> {
+    Cell::new("/paused")
+}
+
+pub fn pause_reason() -> Cell<String> {
+    Cell::new("/pause_reason")
+}
+
+pub fn breaker_window() -> Cell<u128> {
+    Cell::new("/breaker/window")
+}
+
+pub fn breaker_max_win_ratio_bps() -> Cell<u128> {
+    Cell::new("/breaker/max_win_ratio_bps")
+}
+
+pub fn breaker_position() -> Cell<u128> {
+    Cell::new("/breaker/position")
+}
+
+pub fn breaker_filled() -> Cell<u128> {
+    Cell::new("/breaker/filled")
+}
+
+pub fn breaker_recent_wins() -> Cell<u128> {
+    Cell::new("/breaker/recent_wins")
+}
+
+/// Ring buffer of recent outcomes, one slot per coupon in the window
+pub fn breaker_outcomes() -> Map<u128, bool> {
+    Map::new("/breaker/outcomes")
+}
+
+// Coupon registry and per-coupon records
+
+/// Lifecycle status of every coupon the factory minted; unset for anything else
+pub fn coupon_status() -> Map<AlkaneId, u8> {
+    Map::new("/registered_coupons")
+}
+
+pub fn registered_coupon_list() -> IndexedList<AlkaneId> {
+    IndexedList::new("/registered_coupons_count", "/registered_coupons/index")
+}
+
+pub fn registered_coupon_index() -> Map<AlkaneId, u128> {
+    Map::new("/registered_coupons/index_of")
+}
+
+pub fn coupon_stake_token() -> Map<AlkaneId, AlkaneId> {
+    Map::with_suffix("/coupons", "stake_token")
+}
+
+pub fn coupon_stake_amount() -> Map<AlkaneId, u128> {
+    Map::with_suffix("/coupons", "stake_amount")
+}
+
+pub fn coupon_final_result() -> Map<AlkaneId, u8> {
+    Map::with_suffix("/coupons", "final_result")
+}
+
+pub fn coupon_is_winner() -> Map<AlkaneId, bool> {
+    Map::with_suffix("/coupons", "is_winner")
+}
+
+/// Losing coupon that a re-roll burned to mint this one
+pub fn coupon_parent() -> Map<AlkaneId, AlkaneId> {
+    Map::with_suffix("/coupons", "parent")
+}
+
+/// Coupons burned to craft this one; empty for rolled coupons
+pub fn craft_inputs(coupon_id: &AlkaneId) -> IndexedList<AlkaneId> {
+    IndexedList::under(format!("/coupons/{}/craft_inputs", coupon_id.fragment()))
+}
+
+/// Outcome tier index at mint time
+pub fn coupon_tier() -> Map<AlkaneId, u8> {
+    Map::with_suffix("/coupons", "tier")
+}
+
+/// Creation txid in internal byte order
+pub fn coupon_txid() -> Map<AlkaneId, [u8; 32]> {
+    Map::with_suffix("/coupons", "txid")
+}
+
+/// Coupons minted by a transaction; one transaction may mint several
+pub fn coupons_by_txid(txid: &[u8; 32]) -> IndexedList<AlkaneId> {
+    IndexedList::under(format!("/coupons_by_txid/{}", hex::encode(txid)))
+}
+
+/// Replay protection: transactions that have already created coupons
+pub fn processed_txids() -> Map<[u8; 32], bool> {
+    Map::new("/processed_txids")
+}
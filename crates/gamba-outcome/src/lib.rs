@@ -0,0 +1,211 @@
+//! Outcome tiers shared by the factory, the coupon contract and its SVG.
+//!
+//! A coupon's tier is derived from its `final_result` and whether it beat the
+//! success threshold. The factory picks templates and prize vaults by tier, the
+//! coupon names and renders itself by tier, and the rarity score adds a bonus
+//! per tier, so all of them go through [`TierBoundaries::classify`].
+
+use anyhow::{anyhow, Result};
+use gamba_storage::StorageValue;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum OutcomeTier {
+    Lose,
+    SmallWin,
+    Win,
+    BigWin,
+    Jackpot,
+}
+
+impl OutcomeTier {
+    pub const ALL: [OutcomeTier; 5] = [
+        OutcomeTier::Lose,
+        OutcomeTier::SmallWin,
+        OutcomeTier::Win,
+        OutcomeTier::BigWin,
+        OutcomeTier::Jackpot,
+    ];
+
+    /// Index used in opcodes and storage keys, from 0 (lose) to 4 (jackpot)
+    pub fn index(self) -> u128 {
+        self as u128
+    }
+
+    pub fn from_index(index: u128) -> Option<Self> {
+        Self::ALL.get(usize::try_from(index).ok()?).copied()
+    }
+
+    /// Display name, e.g. "Big Win"
+    pub fn name(self) -> &'static str {
+        match self {
+            OutcomeTier::Lose => "Lose",
+            OutcomeTier::SmallWin => "Small Win",
+            OutcomeTier::Win => "Win",
+            OutcomeTier::BigWin => "Big Win",
+            OutcomeTier::Jackpot => "Jackpot",
+        }
+    }
+
+    pub fn is_win(self) -> bool {
+        self != OutcomeTier::Lose
+    }
+
+    /// Points added to the rarity score of a coupon in this tier
+    pub fn rarity_bonus(self) -> u32 {
+        match self {
+            OutcomeTier::Lose => 0,
+            OutcomeTier::SmallWin => 10,
+            OutcomeTier::Win => 25,
+            OutcomeTier::BigWin => 50,
+            OutcomeTier::Jackpot => 100,
+        }
+    }
+}
+
+/// Lowest `final_result` of each winning tier, strictly increasing
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TierBoundaries {
+    pub small_win: u8,
+    pub win: u8,
+    pub big_win: u8,
+    pub jackpot: u8,
+}
+
+impl Default for TierBoundaries {
+    fn default() -> Self {
+        Self {
+            small_win: 170,
+            win: 200,
+            big_win: 230,
+            jackpot: 250,
+        }
+    }
+}
+
+impl TierBoundaries {
+    pub fn new(small_win: u8, win: u8, big_win: u8, jackpot: u8) -> Result<Self> {
+        if !(small_win < win && win < big_win && big_win < jackpot) {
+            return Err(anyhow!(
+                "Tier boundaries must be strictly increasing, got {}, {}, {}, {}",
+                small_win,
+                win,
+                big_win,
+                jackpot
+            ));
+        }
+        Ok(Self {
+            small_win,
+            win,
+            big_win,
+            jackpot,
+        })
+    }
+
+    /// Tier a roll reaches on its own, ignoring the success threshold
+    pub fn band(&self, final_result: u8) -> OutcomeTier {
+        if final_result >= self.jackpot {
+            OutcomeTier::Jackpot
+        } else if final_result >= self.big_win {
+            OutcomeTier::BigWin
+        } else if final_result >= self.win {
+            OutcomeTier::Win
+        } else if final_result >= self.small_win {
+            OutcomeTier::SmallWin
+        } else {
+            OutcomeTier::Lose
+        }
+    }
+
+    /// Tier of a coupon. Losers are always [`OutcomeTier::Lose`]; winners
+    /// below the small win boundary still count as a small win.
+    pub fn classify(&self, final_result: u8, is_winner: bool) -> OutcomeTier {
+        if !is_winner {
+            return OutcomeTier::Lose;
+        }
+        self.band(final_result).max(OutcomeTier::SmallWin)
+    }
+
+    /// Pack into one opcode input: the four boundaries as the low bytes, little-endian
+    pub fn to_u128(&self) -> u128 {
+        u32::from_le_bytes([self.small_win, self.win, self.big_win, self.jackpot]) as u128
+    }
+
+    pub fn from_u128(packed: u128) -> Result<Self> {
+        let packed = u32::try_from(packed)
+            .map_err(|_| anyhow!("Packed tier boundaries must fit in 4 bytes"))?;
+        let [small_win, win, big_win, jackpot] = packed.to_le_bytes();
+        Self::new(small_win, win, big_win, jackpot)
+    }
+}
+
+/// [small_win (1)] + [win (1)] + [big_win (1)] + [jackpot (1)]
+impl StorageValue for TierBoundaries {
+    fn decode(bytes: &[u8]) -> Result<Self> {
+        match bytes {
+            [small_win, win, big_win, jackpot] => Self::new(*small_win, *win, *big_win, *jackpot),
+            _ => Err(anyhow!("Malformed tier boundaries of {} bytes", bytes.len())),
+        }
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        vec![self.small_win, self.win, self.big_win, self.jackpot]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_with_default_boundaries() {
+        let boundaries = TierBoundaries::default();
+
+        assert_eq!(boundaries.classify(255, true), OutcomeTier::Jackpot);
+        assert_eq!(boundaries.classify(250, true), OutcomeTier::Jackpot);
+        assert_eq!(boundaries.classify(249, true), OutcomeTier::BigWin);
+        assert_eq!(boundaries.classify(225, true), OutcomeTier::Win);
+        assert_eq!(boundaries.classify(170, true), OutcomeTier::SmallWin);
+
+        // Winners below the first boundary are still winners
+        assert_eq!(boundaries.classify(150, true), OutcomeTier::SmallWin);
+
+        // Losers never get a winning tier, however high the roll
+        assert_eq!(boundaries.classify(255, false), OutcomeTier::Lose);
+        assert_eq!(boundaries.band(255), OutcomeTier::Jackpot);
+    }
+
+    #[test]
+    fn test_custom_boundaries() {
+        let boundaries = TierBoundaries::new(150, 180, 210, 240).unwrap();
+
+        assert_eq!(boundaries.classify(240, true), OutcomeTier::Jackpot);
+        assert_eq!(boundaries.classify(239, true), OutcomeTier::BigWin);
+        assert_eq!(boundaries.classify(180, true), OutcomeTier::Win);
+    }
+
+    #[test]
+    fn test_rejects_unordered_boundaries() {
+        assert!(TierBoundaries::new(200, 200, 230, 250).is_err());
+        assert!(TierBoundaries::new(170, 230, 200, 250).is_err());
+        assert!(TierBoundaries::decode(&[170, 200, 230]).is_err());
+        assert!(TierBoundaries::decode(&[250, 230, 200, 170]).is_err());
+    }
+
+    #[test]
+    fn test_packing_round_trip() {
+        let boundaries = TierBoundaries::new(150, 180, 210, 240).unwrap();
+
+        assert_eq!(TierBoundaries::from_u128(boundaries.to_u128()).unwrap(), boundaries);
+        assert_eq!(TierBoundaries::decode(&boundaries.encode()).unwrap(), boundaries);
+        assert!(TierBoundaries::from_u128(boundaries.to_u128() | (1 << 40)).is_err());
+    }
+
+    #[test]
+    fn test_index_round_trip() {
+        for tier in OutcomeTier::ALL {
+            assert_eq!(OutcomeTier::from_index(tier.index()), Some(tier));
+        }
+        assert_eq!(OutcomeTier::from_index(5), None);
+        assert_eq!(OutcomeTier::Jackpot.name(), "Jackpot");
+    }
+}
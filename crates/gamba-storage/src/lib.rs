@@ -0,0 +1,496 @@
+//! Typed storage cells shared by the gamba contracts.
+//!
+//! Contracts expose their key-value storage through [`Store`]. Values are read
+//! and written through typed handles ([`Cell`], [`IndexedList`], [`Map`]) that
+//! keep the byte layout in one place:
+//!
+//! - `u8` and `bool` are a single byte (`bool` must be 0 or 1)
+//! - `u128` is 16 little-endian bytes
+//! - `AlkaneId` is 32 bytes: block (16) then tx (16)
+//! - `[u8; 32]` hashes such as txids are stored as-is
+//! - `Vec<AlkaneId>` is a packed run of 32-byte ids
+//! - `Vec<u8>` and `String` are stored as-is
+//!
+//! An empty value means the key is unset. Values of the wrong length are
+//! reported as errors instead of being read as zero, and counter updates fail
+//! on overflow instead of wrapping.
+
+use alkanes_support::id::AlkaneId;
+use anyhow::{anyhow, Result};
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::marker::PhantomData;
+
+/// Raw key-value access to a contract's storage
+pub trait Store {
+    fn load_raw(&self, key: &str) -> Vec<u8>;
+    fn store_raw(&self, key: &str, value: Vec<u8>);
+}
+
+/// In-memory [`Store`], for exercising storage logic outside the runtime
+#[derive(Default)]
+pub struct MemoryStore(RefCell<BTreeMap<String, Vec<u8>>>);
+
+impl MemoryStore {
+    /// Number of keys written so far, including cleared ones
+    pub fn len(&self) -> usize {
+        self.0.borrow().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.borrow().is_empty()
+    }
+}
+
+impl Store for MemoryStore {
+    fn load_raw(&self, key: &str) -> Vec<u8> {
+        self.0.borrow().get(key).cloned().unwrap_or_default()
+    }
+
+    fn store_raw(&self, key: &str, value: Vec<u8>) {
+        self.0.borrow_mut().insert(key.to_string(), value);
+    }
+}
+
+/// A value with a fixed byte encoding in storage
+pub trait StorageValue: Sized {
+    /// Decode a non-empty stored value
+    fn decode(bytes: &[u8]) -> Result<Self>;
+    fn encode(&self) -> Vec<u8>;
+}
+
+fn expect_len(bytes: &[u8], len: usize, what: &str) -> Result<()> {
+    if bytes.len() != len {
+        return Err(anyhow!(
+            "Malformed {}: expected {} bytes, found {}",
+            what,
+            len,
+            bytes.len()
+        ));
+    }
+    Ok(())
+}
+
+impl StorageValue for u8 {
+    fn decode(bytes: &[u8]) -> Result<Self> {
+        expect_len(bytes, 1, "u8")?;
+        Ok(bytes[0])
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        vec![*self]
+    }
+}
+
+impl StorageValue for bool {
+    fn decode(bytes: &[u8]) -> Result<Self> {
+        expect_len(bytes, 1, "bool")?;
+        match bytes[0] {
+            0 => Ok(false),
+            1 => Ok(true),
+            other => Err(anyhow!("Malformed bool: expected 0 or 1, found {}", other)),
+        }
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        vec![u8::from(*self)]
+    }
+}
+
+impl StorageValue for u128 {
+    fn decode(bytes: &[u8]) -> Result<Self> {
+        expect_len(bytes, 16, "u128")?;
+        Ok(u128::from_le_bytes(bytes.try_into()?))
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        self.to_le_bytes().to_vec()
+    }
+}
+
+impl StorageValue for AlkaneId {
+    fn decode(bytes: &[u8]) -> Result<Self> {
+        expect_len(bytes, 32, "AlkaneId")?;
+        Ok(AlkaneId {
+            block: u128::from_le_bytes(bytes[0..16].try_into()?),
+            tx: u128::from_le_bytes(bytes[16..32].try_into()?),
+        })
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(32);
+        bytes.extend_from_slice(&self.block.to_le_bytes());
+        bytes.extend_from_slice(&self.tx.to_le_bytes());
+        bytes
+    }
+}
+
+impl StorageValue for [u8; 32] {
+    fn decode(bytes: &[u8]) -> Result<Self> {
+        expect_len(bytes, 32, "32-byte hash")?;
+        Ok(bytes.try_into()?)
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        self.to_vec()
+    }
+}
+
+impl StorageValue for Vec<AlkaneId> {
+    fn decode(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() % 32 != 0 {
+            return Err(anyhow!(
+                "Malformed AlkaneId list: {} bytes is not a multiple of 32",
+                bytes.len()
+            ));
+        }
+        bytes.chunks_exact(32).map(AlkaneId::decode).collect()
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        self.iter().flat_map(|id| id.encode()).collect()
+    }
+}
+
+impl StorageValue for Vec<u8> {
+    fn decode(bytes: &[u8]) -> Result<Self> {
+        Ok(bytes.to_vec())
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        self.clone()
+    }
+}
+
+impl StorageValue for String {
+    fn decode(bytes: &[u8]) -> Result<Self> {
+        String::from_utf8(bytes.to_vec()).map_err(|_| anyhow!("Malformed string: invalid UTF-8"))
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        self.as_bytes().to_vec()
+    }
+}
+
+/// A single typed value under a fixed key
+pub struct Cell<T> {
+    key: String,
+    _value: PhantomData<T>,
+}
+
+impl<T: StorageValue> Cell<T> {
+    pub fn new(key: impl Into<String>) -> Self {
+        Self {
+            key: key.into(),
+            _value: PhantomData,
+        }
+    }
+
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    pub fn is_set<S: Store + ?Sized>(&self, store: &S) -> bool {
+        !store.load_raw(&self.key).is_empty()
+    }
+
+    /// `None` if unset, an error if the stored bytes do not decode
+    pub fn get<S: Store + ?Sized>(&self, store: &S) -> Result<Option<T>> {
+        let bytes = store.load_raw(&self.key);
+        if bytes.is_empty() {
+            return Ok(None);
+        }
+        T::decode(&bytes)
+            .map(Some)
+            .map_err(|error| anyhow!("{} at {}", error, self.key))
+    }
+
+    /// Like [`Cell::get`], but an unset value is an error
+    pub fn require<S: Store + ?Sized>(&self, store: &S) -> Result<T> {
+        self.get(store)?
+            .ok_or_else(|| anyhow!("Missing value at {}", self.key))
+    }
+
+    pub fn set<S: Store + ?Sized>(&self, store: &S, value: &T) {
+        store.store_raw(&self.key, value.encode());
+    }
+
+    pub fn clear<S: Store + ?Sized>(&self, store: &S) {
+        store.store_raw(&self.key, Vec::new());
+    }
+}
+
+impl<T: StorageValue + Default> Cell<T> {
+    pub fn get_or_default<S: Store + ?Sized>(&self, store: &S) -> Result<T> {
+        Ok(self.get(store)?.unwrap_or_default())
+    }
+}
+
+impl Cell<u128> {
+    /// Add to the stored value, failing rather than wrapping on overflow.
+    /// Returns the new value.
+    pub fn add<S: Store + ?Sized>(&self, store: &S, amount: u128) -> Result<u128> {
+        let value = self
+            .get_or_default(store)?
+            .checked_add(amount)
+            .ok_or_else(|| anyhow!("Overflow adding {} at {}", amount, self.key))?;
+        self.set(store, &value);
+        Ok(value)
+    }
+
+    /// Subtract from the stored value, failing on underflow. Returns the new value.
+    pub fn sub<S: Store + ?Sized>(&self, store: &S, amount: u128) -> Result<u128> {
+        let value = self
+            .get_or_default(store)?
+            .checked_sub(amount)
+            .ok_or_else(|| anyhow!("Underflow subtracting {} at {}", amount, self.key))?;
+        self.set(store, &value);
+        Ok(value)
+    }
+
+    pub fn increment<S: Store + ?Sized>(&self, store: &S) -> Result<u128> {
+        self.add(store, 1)
+    }
+}
+
+/// An append-only list stored as a count plus one key per index
+pub struct IndexedList<T> {
+    count: Cell<u128>,
+    item_prefix: String,
+    _value: PhantomData<T>,
+}
+
+impl<T: StorageValue> IndexedList<T> {
+    /// Items live under `{item_prefix}/{index}`, the length under `count_key`
+    pub fn new(count_key: impl Into<String>, item_prefix: impl Into<String>) -> Self {
+        Self {
+            count: Cell::new(count_key),
+            item_prefix: item_prefix.into(),
+            _value: PhantomData,
+        }
+    }
+
+    /// A list whose length is kept under `{prefix}/count`
+    pub fn under(prefix: impl Into<String>) -> Self {
+        let prefix = prefix.into();
+        Self::new(format!("{}/count", prefix), prefix)
+    }
+
+    pub fn item(&self, index: u128) -> Cell<T> {
+        Cell::new(format!("{}/{}", self.item_prefix, index))
+    }
+
+    pub fn len<S: Store + ?Sized>(&self, store: &S) -> Result<u128> {
+        self.count.get_or_default(store)
+    }
+
+    pub fn is_empty<S: Store + ?Sized>(&self, store: &S) -> Result<bool> {
+        Ok(self.len(store)? == 0)
+    }
+
+    /// `None` past the end of the list
+    pub fn get<S: Store + ?Sized>(&self, store: &S, index: u128) -> Result<Option<T>> {
+        if index >= self.len(store)? {
+            return Ok(None);
+        }
+        self.item(index).require(store).map(Some)
+    }
+
+    /// Append a value and return its index
+    pub fn push<S: Store + ?Sized>(&self, store: &S, value: &T) -> Result<u128> {
+        let index = self.len(store)?;
+        self.item(index).set(store, value);
+        self.count.increment(store)?;
+        Ok(index)
+    }
+
+    /// Overwrite an existing entry
+    pub fn set<S: Store + ?Sized>(&self, store: &S, index: u128, value: &T) -> Result<()> {
+        let len = self.len(store)?;
+        if index >= len {
+            return Err(anyhow!(
+                "Index {} out of bounds for {} entries at {}",
+                index,
+                len,
+                self.item_prefix
+            ));
+        }
+        self.item(index).set(store, value);
+        Ok(())
+    }
+
+    /// Up to `limit` entries starting at `offset`
+    pub fn page<S: Store + ?Sized>(&self, store: &S, offset: u128, limit: u128) -> Result<Vec<T>> {
+        let end = offset.saturating_add(limit).min(self.len(store)?);
+        (offset..end).map(|index| self.item(index).require(store)).collect()
+    }
+
+    pub fn all<S: Store + ?Sized>(&self, store: &S) -> Result<Vec<T>> {
+        self.page(store, 0, u128::MAX)
+    }
+}
+
+/// A key that can address an entry of a [`Map`]
+pub trait MapKey {
+    fn fragment(&self) -> String;
+}
+
+impl MapKey for u128 {
+    fn fragment(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl MapKey for AlkaneId {
+    fn fragment(&self) -> String {
+        format!("{}_{}", self.block, self.tx)
+    }
+}
+
+impl MapKey for [u8; 32] {
+    fn fragment(&self) -> String {
+        hex::encode(self)
+    }
+}
+
+impl MapKey for str {
+    fn fragment(&self) -> String {
+        self.to_string()
+    }
+}
+
+/// Typed values addressed by key, stored under `{prefix}/{key}[/{suffix}]`
+pub struct Map<K: ?Sized, V> {
+    prefix: String,
+    suffix: Option<String>,
+    _entry: PhantomData<fn(&K) -> V>,
+}
+
+impl<K: MapKey + ?Sized, V: StorageValue> Map<K, V> {
+    pub fn new(prefix: impl Into<String>) -> Self {
+        Self {
+            prefix: prefix.into(),
+            suffix: None,
+            _entry: PhantomData,
+        }
+    }
+
+    /// One field of a per-key record, e.g. `/coupons/{id}/stake_amount`
+    pub fn with_suffix(prefix: impl Into<String>, suffix: impl Into<String>) -> Self {
+        Self {
+            prefix: prefix.into(),
+            suffix: Some(suffix.into()),
+            _entry: PhantomData,
+        }
+    }
+
+    pub fn entry(&self, key: &K) -> Cell<V> {
+        match &self.suffix {
+            Some(suffix) => Cell::new(format!("{}/{}/{}", self.prefix, key.fragment(), suffix)),
+            None => Cell::new(format!("{}/{}", self.prefix, key.fragment())),
+        }
+    }
+
+    pub fn contains<S: Store + ?Sized>(&self, store: &S, key: &K) -> bool {
+        self.entry(key).is_set(store)
+    }
+
+    pub fn get<S: Store + ?Sized>(&self, store: &S, key: &K) -> Result<Option<V>> {
+        self.entry(key).get(store)
+    }
+
+    pub fn set<S: Store + ?Sized>(&self, store: &S, key: &K, value: &V) {
+        self.entry(key).set(store, value)
+    }
+
+    pub fn remove<S: Store + ?Sized>(&self, store: &S, key: &K) {
+        self.entry(key).clear(store)
+    }
+}
+
+impl<K: MapKey + ?Sized, V: StorageValue + Default> Map<K, V> {
+    pub fn get_or_default<S: Store + ?Sized>(&self, store: &S, key: &K) -> Result<V> {
+        self.entry(key).get_or_default(store)
+    }
+}
+
+impl<K: MapKey + ?Sized> Map<K, u128> {
+    pub fn add<S: Store + ?Sized>(&self, store: &S, key: &K, amount: u128) -> Result<u128> {
+        self.entry(key).add(store, amount)
+    }
+
+    pub fn sub<S: Store + ?Sized>(&self, store: &S, key: &K, amount: u128) -> Result<u128> {
+        self.entry(key).sub(store, amount)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_values_round_trip() {
+        let store = MemoryStore::default();
+        let id = AlkaneId { block: 2, tx: 7 };
+
+        Cell::new("/u8").set(&store, &200u8);
+        Cell::new("/bool").set(&store, &true);
+        Cell::new("/u128").set(&store, &(u128::MAX - 1));
+        Cell::new("/id").set(&store, &id);
+        Cell::new("/ids").set(&store, &vec![id.clone(), AlkaneId { block: 4, tx: 1 }]);
+
+        assert_eq!(Cell::<u8>::new("/u8").get(&store).unwrap(), Some(200));
+        assert_eq!(Cell::<bool>::new("/bool").get(&store).unwrap(), Some(true));
+        assert_eq!(Cell::<u128>::new("/u128").get(&store).unwrap(), Some(u128::MAX - 1));
+        assert_eq!(Cell::<AlkaneId>::new("/id").get(&store).unwrap(), Some(id));
+        assert_eq!(Cell::<Vec<AlkaneId>>::new("/ids").get(&store).unwrap().unwrap().len(), 2);
+        assert_eq!(Cell::<u128>::new("/unset").get(&store).unwrap(), None);
+    }
+
+    #[test]
+    fn test_malformed_values_are_errors() {
+        let store = MemoryStore::default();
+        store.store_raw("/short", vec![1u8; 8]);
+        store.store_raw("/flag", vec![2u8]);
+        store.store_raw("/ids", vec![0u8; 40]);
+
+        assert!(Cell::<u128>::new("/short").get(&store).is_err());
+        assert!(Cell::<AlkaneId>::new("/short").get(&store).is_err());
+        assert!(Cell::<bool>::new("/flag").get(&store).is_err());
+        assert!(Cell::<Vec<AlkaneId>>::new("/ids").get(&store).is_err());
+        assert!(Cell::<u128>::new("/missing").require(&store).is_err());
+    }
+
+    #[test]
+    fn test_counters_fail_on_overflow() {
+        let store = MemoryStore::default();
+        let counter = Cell::<u128>::new("/counter");
+
+        assert_eq!(counter.increment(&store).unwrap(), 1);
+        assert!(counter.sub(&store, 2).is_err());
+
+        counter.set(&store, &u128::MAX);
+        assert!(counter.increment(&store).is_err());
+        assert_eq!(counter.get(&store).unwrap(), Some(u128::MAX));
+    }
+
+    #[test]
+    fn test_indexed_list_and_map() {
+        let store = MemoryStore::default();
+        let list = IndexedList::<u128>::under("/list");
+
+        assert_eq!(list.push(&store, &10).unwrap(), 0);
+        assert_eq!(list.push(&store, &11).unwrap(), 1);
+        assert_eq!(list.len(&store).unwrap(), 2);
+        assert_eq!(list.get(&store, 1).unwrap(), Some(11));
+        assert_eq!(list.get(&store, 2).unwrap(), None);
+        assert_eq!(list.page(&store, 1, 5).unwrap(), vec![11]);
+        assert_eq!(store.load_raw("/list/count"), 2u128.encode());
+
+        let balances = Map::<AlkaneId, u128>::with_suffix("/volume", "staked");
+        let token = AlkaneId { block: 2, tx: 1 };
+        balances.add(&store, &token, 5).unwrap();
+        assert_eq!(store.load_raw("/volume/2_1/staked"), 5u128.encode());
+        assert!(balances.sub(&store, &token, 6).is_err());
+    }
+}
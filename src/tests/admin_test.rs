@@ -0,0 +1,52 @@
+use anyhow::Result;
+use wasm_bindgen_test::wasm_bindgen_test;
+
+use crate::tests::factory_harness::*;
+
+#[wasm_bindgen_test]
+fn test_admin_setters_require_auth_token() -> Result<()> {
+    let mut harness = FactoryHarness::initialized()?;
+
+    harness.query(vec![73u128, 100u128])?.expect_revert("Auth token for role 1 is required")?;
+    harness.query(vec![70u128, 2u128, 1u128, 1u128, 1u128])?.expect_revert("Auth token for role 2 is required")?;
+    assert_eq!(harness.query(vec![21u128])?.u128()?, TEST_THRESHOLD);
+
+    // With no timelock the change applies at once and the tokens are handed back
+    harness.call_with_roles(vec![73u128, 100u128])?.ok()?;
+    assert_eq!(harness.query(vec![21u128])?.u128()?, 100);
+    for token in harness.role_tokens.clone() {
+        assert_eq!(balance(&harness.roles, &token)?, 1);
+    }
+
+    harness
+        .call_with_roles(vec![73u128, 255u128])?
+        .expect_revert("Success threshold must be below 255")?;
+    assert_eq!(harness.query(vec![21u128])?.u128()?, 100);
+
+    assert_eq!(harness.query(vec![22u128])?.id()?, harness.role_tokens[ROLE_ADMIN as usize]);
+
+    Ok(())
+}
+
+#[wasm_bindgen_test]
+fn test_stake_limits_set_minimum_stake() -> Result<()> {
+    let mut harness = FactoryHarness::initialized()?;
+    assert_eq!(harness.query(vec![51u128])?.u128()?, 0);
+
+    harness.call_with_roles(vec![74u128, 1_000u128, 0u128])?.ok()?;
+    assert_eq!(harness.query(vec![51u128])?.u128()?, 1_000);
+    let limits = harness.query(vec![52u128])?;
+    assert_eq!(u128_at(limits.data()?, 0)?, 1_000);
+    assert_eq!(u128_at(limits.data()?, 1)?, 0);
+
+    let (_, stake) = harness.new_token(LOSING_STAKE)?;
+    let refused = harness.call(&[stake], factory_cellpack(vec![1u128]))?;
+    refused.expect_revert("Stake 500 is below the minimum of 1000")?;
+
+    harness
+        .call_with_roles(vec![74u128, 2_000u128, 1_000u128])?
+        .expect_revert("Maximum stake 1000 is below the minimum stake 2000")?;
+    assert_eq!(harness.query(vec![51u128])?.u128()?, 1_000);
+
+    Ok(())
+}
@@ -0,0 +1,94 @@
+use alkanes_support::id::AlkaneId;
+use anyhow::{anyhow, Result};
+use wasm_bindgen_test::wasm_bindgen_test;
+
+use crate::tests::factory_harness::*;
+
+/// Split a GetConfig response into its (tag, value) entries
+fn decode_config(data: &[u8]) -> Result<Vec<(u16, Vec<u8>)>> {
+    let length = u32::from_le_bytes(data.get(..4).ok_or_else(|| anyhow!("No length"))?.try_into()?) as usize;
+    assert_eq!(length, data.len() - 4);
+    assert_eq!(data[4], 1, "config version");
+
+    let mut entries = Vec::new();
+    let mut offset = 5;
+    while offset < data.len() {
+        let header = data.get(offset..offset + 4).ok_or_else(|| anyhow!("Truncated entry header"))?;
+        let tag = u16::from_le_bytes([header[0], header[1]]);
+        let value_length = u16::from_le_bytes([header[2], header[3]]) as usize;
+        let value = data
+            .get(offset + 4..offset + 4 + value_length)
+            .ok_or_else(|| anyhow!("Truncated value of tag {}", tag))?;
+        entries.push((tag, value.to_vec()));
+        offset += 4 + value_length;
+    }
+    Ok(entries)
+}
+
+fn entry(entries: &[(u16, Vec<u8>)], tag: u16) -> Option<Vec<u8>> {
+    entries.iter().find(|(entry_tag, _)| *entry_tag == tag).map(|(_, value)| value.clone())
+}
+
+fn id_bytes(id: &AlkaneId) -> Vec<u8> {
+    let mut bytes = id.block.to_le_bytes().to_vec();
+    bytes.extend_from_slice(&id.tx.to_le_bytes());
+    bytes
+}
+
+#[wasm_bindgen_test]
+fn test_get_config_decodes_initial_settings() -> Result<()> {
+    let mut harness = FactoryHarness::initialized()?;
+    let entries = decode_config(harness.query(vec![43u128])?.data()?)?;
+
+    let tags = entries.iter().map(|(tag, _)| *tag).collect::<Vec<u16>>();
+    assert_eq!(tags, vec![1, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 14, 15]);
+
+    assert_eq!(entry(&entries, 1), Some(id_bytes(&COUPON_TEMPLATE_ID)));
+    assert_eq!(entry(&entries, 3), Some(TEST_THRESHOLD.to_le_bytes().to_vec()));
+    assert_eq!(entry(&entries, 4), Some(vec![0u8; 32]));
+    assert_eq!(entry(&entries, 5), Some(vec![]));
+    assert_eq!(entry(&entries, 6), Some(0u128.to_le_bytes().to_vec()));
+    assert_eq!(entry(&entries, 7), Some(vec![0u8]));
+    assert_eq!(entry(&entries, 8), Some(vec![0u8]));
+    assert_eq!(entry(&entries, 9), Some(vec![0u8; 64]));
+    assert_eq!(entry(&entries, 10), Some(0u128.to_le_bytes().to_vec()));
+    assert_eq!(entry(&entries, 11), Some(vec![0u8; 32]));
+    assert_eq!(entry(&entries, 12), Some(1u128.to_le_bytes().to_vec()));
+    assert_eq!(entry(&entries, 15), Some(vec![10u8]));
+
+    Ok(())
+}
+
+#[wasm_bindgen_test]
+fn test_get_config_follows_setters() -> Result<()> {
+    let mut harness = FactoryHarness::initialized()?;
+    let alt = ALT_COUPON_TEMPLATE_ID;
+    let (prize, _) = harness.new_token(1_000)?;
+
+    harness.call_with_roles(vec![88u128, 4u128, alt.block, alt.tx])?.ok()?;
+    harness.call_with_roles(vec![93u128, alt.block, alt.tx])?.ok()?;
+    harness.call_with_roles(vec![80u128, 250u128])?.ok()?;
+    harness.call_with_roles(vec![70u128, prize.block, prize.tx, 3u128, 4u128])?.ok()?;
+    harness.call_with_roles(vec![77u128])?.ok()?;
+
+    let entries = decode_config(harness.query(vec![43u128])?.data()?)?;
+    let tags = entries.iter().map(|(tag, _)| *tag).collect::<Vec<u16>>();
+    assert_eq!(tags, vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 14, 15, 16]);
+
+    let mut tier_template = vec![4u8];
+    tier_template.extend(id_bytes(&alt));
+    assert_eq!(entry(&entries, 2), Some(tier_template));
+    assert_eq!(entry(&entries, 6), Some(250u128.to_le_bytes().to_vec()));
+
+    let mut pause_state = vec![1u8];
+    pause_state.extend_from_slice(b"Paused by admin");
+    assert_eq!(entry(&entries, 7), Some(pause_state));
+
+    let mut payout = id_bytes(&prize);
+    payout.extend_from_slice(&3u128.to_le_bytes());
+    payout.extend_from_slice(&4u128.to_le_bytes());
+    assert_eq!(entry(&entries, 9), Some(payout));
+    assert_eq!(entry(&entries, 16), Some(id_bytes(&alt)));
+
+    Ok(())
+}
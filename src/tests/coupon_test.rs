@@ -0,0 +1,102 @@
+use anyhow::Result;
+use bitcoin::hashes::Hash;
+use wasm_bindgen_test::wasm_bindgen_test;
+
+use crate::tests::factory_harness::*;
+
+#[wasm_bindgen_test]
+fn test_coupon_verifies_itself_against_the_factory() -> Result<()> {
+    let mut harness = FactoryHarness::initialized()?;
+    let coupon = harness.winning_coupon()?;
+
+    // [authentic (16)] + [factory lifecycle status (16)]
+    let verification = harness.query_coupon(&coupon.id, vec![20u128])?;
+    assert_eq!(u128_at(verification.data()?, 0)?, 1);
+    assert_eq!(u128_at(verification.data()?, 1)?, COUPON_ACTIVE);
+
+    harness.call(&[coupon.outpoint], factory_cellpack(vec![6u128]))?.ok()?;
+    let verification = harness.query_coupon(&coupon.id, vec![20u128])?;
+    assert_eq!(u128_at(verification.data()?, 0)?, 1);
+    assert_eq!(u128_at(verification.data()?, 1)?, COUPON_BURNED);
+
+    Ok(())
+}
+
+#[wasm_bindgen_test]
+fn test_coupon_redeem_forwards_to_the_factory() -> Result<()> {
+    let mut harness = FactoryHarness::initialized()?;
+    let (prize, prize_outpoint) = harness.new_token(1_000_000)?;
+    harness.call_with_roles(vec![70u128, prize.block, prize.tx, 1u128, 1u128])?.ok()?;
+    harness.call(&[prize_outpoint], factory_cellpack(vec![3u128]))?.ok()?;
+
+    let coupon = harness.winning_coupon()?;
+    let mut redeem_cellpack = vec![coupon.id.block, coupon.id.tx, 30u128];
+    let redeem = harness.call(&[coupon.outpoint], redeem_cellpack.clone())?;
+    assert_eq!(redeem.u128()?, WINNING_STAKE);
+    assert_eq!(balance(&redeem.outpoint, &prize)?, WINNING_STAKE);
+    assert_eq!(balance(&redeem.outpoint, &coupon.id)?, 0);
+    assert_eq!(harness.coupon_status(&coupon.id)?, COUPON_REDEEMED);
+    assert_eq!(harness.query_coupon(&coupon.id, vec![21u128])?.u128()?, COUPON_REDEEMED);
+
+    // A factory refusal reverts the coupon call and refunds the unit
+    let unpaid = harness.winning_coupon()?;
+    redeem_cellpack[..2].copy_from_slice(&[unpaid.id.block, unpaid.id.tx]);
+    harness.call_with_roles(vec![70u128, prize.block, prize.tx, 10u128, 1u128])?.ok()?;
+    let refused = harness.call(&[unpaid.outpoint], redeem_cellpack)?;
+    refused.expect_revert("Insufficient prize inventory")?;
+    assert_eq!(balance(&refused.outpoint, &unpaid.id)?, 1);
+    assert_eq!(harness.query_coupon(&unpaid.id, vec![21u128])?.u128()?, COUPON_ACTIVE);
+
+    Ok(())
+}
+
+#[wasm_bindgen_test]
+fn test_coupon_burn_forwards_to_the_factory() -> Result<()> {
+    let mut harness = FactoryHarness::initialized()?;
+    let coupon = harness.winning_coupon()?;
+
+    let burn = harness.call(&[coupon.outpoint], vec![coupon.id.block, coupon.id.tx, 31u128])?;
+    burn.ok()?;
+    assert_eq!(balance(&burn.outpoint, &coupon.id)?, 0);
+    assert_eq!(harness.coupon_status(&coupon.id)?, COUPON_BURNED);
+    assert_eq!(harness.query_coupon(&coupon.id, vec![21u128])?.u128()?, COUPON_BURNED);
+
+    Ok(())
+}
+
+#[wasm_bindgen_test]
+fn test_coupon_status_follows_factory_retirement() -> Result<()> {
+    let mut harness = FactoryHarness::initialized()?;
+    let coupon = harness.winning_coupon()?;
+
+    harness.call_with_roles(vec![90u128, coupon.id.block, coupon.id.tx, COUPON_VOID])?.ok()?;
+    assert_eq!(harness.query_coupon(&coupon.id, vec![21u128])?.u128()?, COUPON_VOID);
+
+    // The coupon itself still thinks it is active, but the factory refuses the burn
+    let burn = harness.call(&[coupon.outpoint], vec![coupon.id.block, coupon.id.tx, 31u128])?;
+    burn.expect_revert("is void")?;
+    assert_eq!(balance(&burn.outpoint, &coupon.id)?, 1);
+
+    Ok(())
+}
+
+#[wasm_bindgen_test]
+fn test_coupon_records_its_provenance() -> Result<()> {
+    let mut harness = FactoryHarness::initialized()?;
+    let (_, stake) = harness.new_token(2 * WINNING_STAKE)?;
+    let batch = harness.call(&[stake], factory_cellpack(vec![5u128, 2u128]))?;
+    let coupons = batch.returned()?;
+
+    // [txid (32)] + [merkle_root (32)] + [entropy_index (16)] + [success_threshold (16)] + [creation_block (16)]
+    for (index, coupon) in coupons.iter().enumerate() {
+        let provenance = harness.query_coupon(&coupon.id, vec![24u128])?;
+        let provenance = provenance.data()?;
+        assert_eq!(&provenance[..32], batch.txid.as_byte_array().as_slice());
+        assert_eq!(u128_at(&provenance[64..], 0)?, index as u128);
+        assert_eq!(u128_at(&provenance[64..], 1)?, TEST_THRESHOLD);
+        assert_eq!(u128_at(&provenance[64..], 2)?, batch.height as u128);
+        assert_eq!(harness.query_coupon(&coupon.id, vec![15u128])?.u128()?, batch.height as u128);
+    }
+
+    Ok(())
+}
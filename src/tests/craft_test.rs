@@ -0,0 +1,83 @@
+use alkanes_support::id::AlkaneId;
+use anyhow::Result;
+use wasm_bindgen_test::wasm_bindgen_test;
+
+use crate::tests::factory_harness::*;
+
+#[wasm_bindgen_test]
+fn test_craft_consumes_losing_coupons() -> Result<()> {
+    let mut harness = FactoryHarness::initialized()?;
+    let (dust, _) = harness.new_token(LOSING_STAKE)?;
+    let inputs = (0..3)
+        .map(|_| harness.losing_coupon(&dust))
+        .collect::<Result<Vec<Coupon>>>()?;
+    let input_ids = inputs.iter().map(|coupon| coupon.id.clone()).collect::<Vec<AlkaneId>>();
+    let registered = harness.query(vec![12u128])?.u128()?;
+
+    let outpoints = inputs.iter().map(|coupon| coupon.outpoint).collect::<Vec<_>>();
+    let craft = harness.call(&outpoints, factory_cellpack(vec![8u128]))?;
+    let crafted = craft.returned()?[0].id.clone();
+    assert_eq!(balance(&craft.outpoint, &crafted)?, 1);
+
+    for input in &input_ids {
+        assert_eq!(balance(&craft.outpoint, input)?, 0);
+        assert_eq!(harness.coupon_status(input)?, COUPON_BURNED);
+    }
+    assert_eq!(id_list_at(harness.query(vec![26u128, crafted.block, crafted.tx])?.data()?, 0)?, input_ids);
+    assert_eq!(id_list_at(harness.query_coupon(&crafted, vec![23u128])?.data()?, 0)?, input_ids);
+
+    // Crafted coupons carry the combined stake but never win
+    assert_eq!(harness.query_coupon(&crafted, vec![11u128])?.u128()?, 3 * LOSING_STAKE);
+    assert_eq!(harness.query_coupon(&crafted, vec![19u128])?.u128()?, 0);
+    assert_eq!(harness.query(vec![12u128])?.u128()?, registered + 1);
+
+    Ok(())
+}
+
+#[wasm_bindgen_test]
+fn test_craft_refuses_invalid_inputs() -> Result<()> {
+    let mut harness = FactoryHarness::initialized()?;
+    let (dust, _) = harness.new_token(LOSING_STAKE)?;
+
+    let single = harness.losing_coupon(&dust)?;
+    let refused = harness.call(&[single.outpoint], factory_cellpack(vec![8u128]))?;
+    refused.expect_revert("Crafting takes 2 to 10 coupons, got 1")?;
+    assert_eq!(balance(&refused.outpoint, &single.id)?, 1);
+
+    let winner = harness.winning_coupon()?;
+    let refused = harness.call(&[refused.outpoint, winner.outpoint], factory_cellpack(vec![8u128]))?;
+    refused.expect_revert("is a winner, only losing coupons can be consumed")?;
+    assert_eq!(balance(&refused.outpoint, &single.id)?, 1);
+    assert_eq!(balance(&refused.outpoint, &winner.id)?, 1);
+    assert_eq!(harness.coupon_status(&single.id)?, COUPON_ACTIVE);
+
+    // A crafted coupon cannot be crafted again
+    let first = harness.losing_coupon(&dust)?;
+    let second = harness.losing_coupon(&dust)?;
+    let craft = harness.call(&[first.outpoint, second.outpoint], factory_cellpack(vec![8u128]))?;
+    let crafted = craft.returned()?[0].id.clone();
+    let extra = harness.losing_coupon(&dust)?;
+    let recraft = harness.call(&[craft.outpoint, extra.outpoint], factory_cellpack(vec![8u128]))?;
+    recraft.expect_revert("is already crafted")?;
+    assert_eq!(balance(&recraft.outpoint, &crafted)?, 1);
+    assert_eq!(harness.coupon_status(&crafted)?, COUPON_ACTIVE);
+
+    Ok(())
+}
+
+#[wasm_bindgen_test]
+fn test_craft_shares_the_replay_guard_with_rolls() -> Result<()> {
+    let mut harness = FactoryHarness::initialized()?;
+    let (_, stake) = harness.new_token(LOSING_STAKE)?;
+
+    // A transaction that already rolled cannot craft as well
+    let calls = harness.call_many(
+        &[stake],
+        vec![factory_cellpack(vec![1u128]), factory_cellpack(vec![8u128])],
+    )?;
+    calls[0].ok()?;
+    calls[1].expect_revert("has already created coupons")?;
+    assert_eq!(harness.query(vec![12u128])?.u128()?, 1);
+
+    Ok(())
+}
@@ -0,0 +1,58 @@
+use alkanes_support::id::AlkaneId;
+use anyhow::Result;
+use wasm_bindgen_test::wasm_bindgen_test;
+
+use crate::tests::factory_harness::*;
+
+#[wasm_bindgen_test]
+fn test_second_roll_in_one_transaction_is_refused() -> Result<()> {
+    let mut harness = FactoryHarness::initialized()?;
+    let (dust, stake) = harness.new_token(LOSING_STAKE)?;
+
+    let calls = harness.call_many(
+        &[stake],
+        vec![factory_cellpack(vec![1u128]), factory_cellpack(vec![1u128])],
+    )?;
+    let coupon = calls[0].returned()?[0].id.clone();
+    calls[1].expect_revert("has already created coupons, use CreateCouponBatch for several")?;
+
+    assert_eq!(balance(&calls[0].outpoint, &coupon)?, 1);
+    assert_eq!(balance(&calls[0].outpoint, &dust)?, 0);
+    assert_eq!(harness.query(vec![12u128])?.u128()?, 1);
+
+    Ok(())
+}
+
+#[wasm_bindgen_test]
+fn test_batch_splits_the_stake_evenly() -> Result<()> {
+    let mut harness = FactoryHarness::initialized()?;
+    let (dust, stake) = harness.new_token(3 * LOSING_STAKE)?;
+
+    let batch = harness.call(&[stake], factory_cellpack(vec![5u128, 3u128]))?;
+    let coupons = batch
+        .returned()?
+        .into_iter()
+        .map(|transfer| transfer.id)
+        .collect::<Vec<AlkaneId>>();
+    assert_eq!(coupons.len(), 3);
+
+    for coupon in &coupons {
+        assert_eq!(balance(&batch.outpoint, coupon)?, 1);
+        assert_eq!(harness.query_coupon(coupon, vec![11u128])?.u128()?, LOSING_STAKE);
+    }
+    let [txid_lo, txid_hi] = txid_halves(&batch.txid);
+    assert_eq!(id_list_at(harness.query(vec![35u128, txid_lo, txid_hi])?.data()?, 0)?, coupons);
+    assert_eq!(harness.query(vec![12u128])?.u128()?, 3);
+
+    // Refused batches refund the stake
+    let stake = harness.mint(&dust)?;
+    let uneven = harness.call(&[stake], factory_cellpack(vec![5u128, 4u128]))?;
+    uneven.expect_revert("Stake 1500 cannot be split evenly across 4 coupons")?;
+    assert_eq!(balance(&uneven.outpoint, &dust)?, 3 * LOSING_STAKE);
+
+    let oversized = harness.call(&[uneven.outpoint], factory_cellpack(vec![5u128, 11u128]))?;
+    oversized.expect_revert("Batch size must be between 1 and 10, got 11")?;
+    assert_eq!(balance(&oversized.outpoint, &dust)?, 3 * LOSING_STAKE);
+
+    Ok(())
+}
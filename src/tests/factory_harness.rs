@@ -0,0 +1,452 @@
+//! Shared setup for the indexer tests of the factory opcodes.
+//!
+//! Every call is a transaction of its own, indexed in a block of its own.
+//! The alkanes held by the spent outpoints flow into the first protostone,
+//! and whatever a call returns or refunds lands on output 0.
+
+use alkanes::indexer::index_block;
+use alkanes::message::AlkaneMessageContext;
+use alkanes::precompiled::free_mint_build;
+use alkanes::tests::helpers as alkane_helpers;
+use alkanes::tests::helpers::clear;
+use alkanes::view;
+use alkanes_support::cellpack::Cellpack;
+use alkanes_support::id::AlkaneId;
+use alkanes_support::parcel::AlkaneTransfer;
+use alkanes_support::response::ExtendedCallResponse;
+use alkanes_support::trace::{Trace, TraceEvent};
+use anyhow::{anyhow, Result};
+use bitcoin::absolute::LockTime;
+use bitcoin::blockdata::transaction::OutPoint;
+use bitcoin::hashes::Hash;
+use bitcoin::{transaction::Version, ScriptBuf, Sequence};
+use bitcoin::{Address, Amount, Transaction, TxIn, TxOut, Txid, Witness};
+use metashrew_support::{index_pointer::KeyValuePointer, utils::consensus_encode};
+use ordinals::Runestone;
+use protobuf::Message;
+use protorune::protostone::Protostones;
+use protorune::test_helpers::{get_btc_network, ADDRESS1};
+use protorune::{balance_sheet::load_sheet, message::MessageContext, tables::RuneTable};
+use protorune::{test_helpers as protorune_helpers};
+use protorune_support::balance_sheet::{BalanceSheetOperations, ProtoruneRuneId};
+use protorune_support::protostone::Protostone;
+use std::str::FromStr;
+
+use crate::precompiled::auth_token_build;
+use crate::precompiled::coupon_template_build;
+use crate::precompiled::factory_build;
+use crate::tests::debug_minimal_test::into_cellpack;
+
+pub const FACTORY_ID: AlkaneId = AlkaneId { block: 4, tx: 0x701 };
+pub const COUPON_TEMPLATE_ID: AlkaneId = AlkaneId { block: 4, tx: 0x601 };
+/// Second copy of the coupon template, for the template setters
+pub const ALT_COUPON_TEMPLATE_ID: AlkaneId = AlkaneId { block: 4, tx: 0x602 };
+const FREE_MINT_TEMPLATE_ID: AlkaneId = AlkaneId { block: 4, tx: 797 };
+
+/// Success threshold used by [`FactoryHarness::initialized`]. Stakes below
+/// 1000 get no bonus and lose unless their roll is 255; a stake of
+/// [`WINNING_STAKE`] maxes out the bonus and always wins.
+pub const TEST_THRESHOLD: u128 = 254;
+pub const WINNING_STAKE: u128 = 255_000;
+pub const LOSING_STAKE: u128 = 500;
+
+pub const ROLE_ADMIN: u128 = 0;
+pub const ROLE_OPERATOR: u128 = 1;
+pub const ROLE_TREASURER: u128 = 2;
+pub const ROLE_PAUSER: u128 = 3;
+
+pub const COUPON_ACTIVE: u128 = 1;
+pub const COUPON_REDEEMED: u128 = 2;
+pub const COUPON_BURNED: u128 = 3;
+pub const COUPON_EXPIRED: u128 = 4;
+pub const COUPON_VOID: u128 = 5;
+
+/// Outcome of one protostone, read back from its trace
+pub struct Call {
+    pub txid: Txid,
+    /// Output 0, which receives everything the call returns or refunds
+    pub outpoint: OutPoint,
+    pub height: u32,
+    response: ExtendedCallResponse,
+    reverted: bool,
+}
+
+impl Call {
+    pub fn ok(&self) -> Result<&Self> {
+        if self.reverted {
+            return Err(anyhow!(
+                "Call reverted: {}",
+                String::from_utf8_lossy(&self.response.data)
+            ));
+        }
+        Ok(self)
+    }
+
+    pub fn data(&self) -> Result<&[u8]> {
+        Ok(self.ok()?.response.data.as_slice())
+    }
+
+    pub fn u128(&self) -> Result<u128> {
+        u128_at(self.data()?, 0)
+    }
+
+    pub fn id(&self) -> Result<AlkaneId> {
+        id_at(self.data()?, 0)
+    }
+
+    pub fn returned(&self) -> Result<Vec<AlkaneTransfer>> {
+        Ok(self.ok()?.response.alkanes.0.clone())
+    }
+
+    /// The revert message must contain `message`
+    pub fn expect_revert(&self, message: &str) -> Result<()> {
+        let text = String::from_utf8_lossy(&self.response.data);
+        if !self.reverted || !text.contains(message) {
+            return Err(anyhow!(
+                "Expected a revert containing {:?}, got {}: {}",
+                message,
+                if self.reverted { "a revert" } else { "success" },
+                text
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Coupon minted by the factory, together with the outpoint holding it
+#[derive(Clone)]
+pub struct Coupon {
+    pub id: AlkaneId,
+    pub outpoint: OutPoint,
+    pub txid: Txid,
+    pub height: u32,
+}
+
+pub struct FactoryHarness {
+    height: u32,
+    /// One token per role, in role order, as minted by Initialize
+    pub role_tokens: Vec<AlkaneId>,
+    /// Outpoint holding all of `role_tokens`
+    pub roles: OutPoint,
+}
+
+impl FactoryHarness {
+    /// Deploy the templates without initializing the factory
+    pub fn deployed() -> Result<Self> {
+        clear();
+
+        let template_block = alkane_helpers::init_with_multiple_cellpacks_with_tx(
+            [
+                free_mint_build::get_bytes(),
+                coupon_template_build::get_bytes(),
+                coupon_template_build::get_bytes(),
+                factory_build::get_bytes(),
+                auth_token_build::get_bytes(),
+            ]
+            .into(),
+            [
+                vec![3u128, 797u128, 101u128],     // free_mint template → deploys at block 4
+                vec![3u128, 0x601, 10u128],        // coupon_token template → deploys at block 4
+                vec![3u128, 0x602, 10u128],        // second coupon_token template → deploys at block 4
+                vec![3u128, 0x701, 10u128],        // coupon_factory template → deploys at block 4
+                vec![3u128, 0xffee, 0u128, 1u128], // auth_token template → deploys at block 4
+            ]
+            .into_iter()
+            .map(|v| into_cellpack(v))
+            .collect::<Vec<Cellpack>>(),
+        );
+        index_block(&template_block, 0)?;
+
+        Ok(Self {
+            height: 0,
+            role_tokens: Vec::new(),
+            roles: OutPoint::null(),
+        })
+    }
+
+    /// Deploy the templates and initialize the factory with [`TEST_THRESHOLD`]
+    pub fn initialized() -> Result<Self> {
+        let mut harness = Self::deployed()?;
+
+        let init = harness.call(
+            &[],
+            vec![
+                FACTORY_ID.block,
+                FACTORY_ID.tx,
+                0u128, // Initialize opcode
+                TEST_THRESHOLD,
+                COUPON_TEMPLATE_ID.block,
+                COUPON_TEMPLATE_ID.tx,
+            ],
+        )?;
+        harness.role_tokens = init.returned()?.into_iter().map(|transfer| transfer.id).collect();
+        harness.roles = init.outpoint;
+        if harness.role_tokens.len() != 4 {
+            return Err(anyhow!("Initialize returned {} role tokens", harness.role_tokens.len()));
+        }
+
+        Ok(harness)
+    }
+
+    /// Height of the block the next call is indexed in
+    pub fn next_height(&self) -> u32 {
+        self.height + 1
+    }
+
+    /// Index the next call at `height`
+    pub fn advance_to(&mut self, height: u32) {
+        self.height = self.height.max(height - 1);
+    }
+
+    pub fn call(&mut self, inputs: &[OutPoint], cellpack: Vec<u128>) -> Result<Call> {
+        Ok(self.call_many(inputs, vec![cellpack])?.remove(0))
+    }
+
+    /// One transaction carrying a protostone per cellpack
+    pub fn call_many(&mut self, inputs: &[OutPoint], cellpacks: Vec<Vec<u128>>) -> Result<Vec<Call>> {
+        self.height += 1;
+        let tx = build_transaction(self.height, inputs, &cellpacks)?;
+        let block = protorune_helpers::create_block_with_txs(vec![tx.clone()]);
+        index_block(&block, self.height)?;
+
+        let txid = tx.compute_txid();
+        (0..cellpacks.len())
+            .map(|index| {
+                // Protostones are numbered after the real outputs and the OP_RETURN
+                let vout = (tx.output.len() + 1 + index) as u32;
+                let (response, reverted) = read_response(txid, vout)?;
+                Ok(Call {
+                    txid,
+                    outpoint: OutPoint { txid, vout: 0 },
+                    height: self.height,
+                    response,
+                    reverted,
+                })
+            })
+            .collect()
+    }
+
+    /// Call the factory with every role token; they end up on the call's output 0
+    pub fn call_with_roles(&mut self, inputs: Vec<u128>) -> Result<Call> {
+        let roles = self.roles;
+        let call = self.call(&[roles], factory_cellpack(inputs))?;
+        self.roles = call.outpoint;
+        Ok(call)
+    }
+
+    /// Call the factory without sending any alkanes
+    pub fn query(&mut self, inputs: Vec<u128>) -> Result<Call> {
+        self.call(&[], factory_cellpack(inputs))
+    }
+
+    /// Call a coupon without sending any alkanes
+    pub fn query_coupon(&mut self, coupon: &AlkaneId, inputs: Vec<u128>) -> Result<Call> {
+        let mut cellpack = vec![coupon.block, coupon.tx];
+        cellpack.extend(inputs);
+        self.call(&[], cellpack)
+    }
+
+    /// Clone the free-mint template into a new token with `units` minted to output 0.
+    /// Each further mint (opcode 77) hands out `units` again.
+    pub fn new_token(&mut self, units: u128) -> Result<(AlkaneId, OutPoint)> {
+        let call = self.call(
+            &[],
+            vec![
+                6u128,
+                FREE_MINT_TEMPLATE_ID.tx,
+                0u128,          // Initialize opcode
+                units,          // token_units
+                units,          // value_per_mint
+                1000000000u128, // cap
+                0x54534554,     // name_part1 ("TEST")
+                0x00000000,     // name_part2 (empty)
+                0x545354,       // symbol ("TST")
+            ],
+        )?;
+        let token = call
+            .returned()?
+            .first()
+            .map(|transfer| transfer.id.clone())
+            .ok_or_else(|| anyhow!("Free mint returned no tokens"))?;
+        Ok((token, call.outpoint))
+    }
+
+    /// Mint another batch of an existing free-mint token
+    pub fn mint(&mut self, token: &AlkaneId) -> Result<OutPoint> {
+        let call = self.call(&[], vec![token.block, token.tx, 77u128])?;
+        call.ok()?;
+        Ok(call.outpoint)
+    }
+
+    /// Stake the alkanes held by `stake` and return the coupon
+    pub fn create_coupon(&mut self, stake: OutPoint) -> Result<Coupon> {
+        let call = self.call(&[stake], factory_cellpack(vec![1u128]))?;
+        let id = call
+            .returned()?
+            .first()
+            .map(|transfer| transfer.id.clone())
+            .ok_or_else(|| anyhow!("CreateCoupon returned no coupon"))?;
+        Ok(Coupon {
+            id,
+            outpoint: call.outpoint,
+            txid: call.txid,
+            height: call.height,
+        })
+    }
+
+    /// A coupon staking [`WINNING_STAKE`] of a fresh token
+    pub fn winning_coupon(&mut self) -> Result<Coupon> {
+        let (_, stake) = self.new_token(WINNING_STAKE)?;
+        self.create_coupon(stake)
+    }
+
+    /// A coupon staking `LOSING_STAKE` units of `token`. The roll alone decides
+    /// the outcome, so the rare winner is set aside and the roll repeated.
+    pub fn losing_coupon(&mut self, token: &AlkaneId) -> Result<Coupon> {
+        for _ in 0..8 {
+            let stake = self.mint(token)?;
+            let coupon = self.create_coupon(stake)?;
+            if self.query_coupon(&coupon.id, vec![19u128])?.u128()? == 0 {
+                return Ok(coupon);
+            }
+        }
+        Err(anyhow!("No losing roll in eight attempts"))
+    }
+
+    pub fn coupon_status(&mut self, coupon: &AlkaneId) -> Result<u128> {
+        self.query(vec![37u128, coupon.block, coupon.tx])?.u128()
+    }
+
+    /// Register a fresh single-unit token for `role`. Its outpoint can then act
+    /// for that role alone, which keeps the other role tokens out of the call.
+    pub fn role_key(&mut self, role: u128) -> Result<(AlkaneId, OutPoint)> {
+        let (key, outpoint) = self.new_token(1)?;
+        self.call_with_roles(vec![85u128, role, key.block, key.tx])?.ok()?;
+        Ok((key, outpoint))
+    }
+}
+
+pub fn factory_cellpack(inputs: Vec<u128>) -> Vec<u128> {
+    let mut cellpack = vec![FACTORY_ID.block, FACTORY_ID.tx];
+    cellpack.extend(inputs);
+    cellpack
+}
+
+fn build_transaction(height: u32, inputs: &[OutPoint], cellpacks: &[Vec<u128>]) -> Result<Transaction> {
+    let inputs = if inputs.is_empty() {
+        vec![OutPoint::null()]
+    } else {
+        inputs.to_vec()
+    };
+
+    let protostones = cellpacks
+        .iter()
+        .map(|cellpack| Protostone {
+            message: into_cellpack(cellpack.clone()).encipher(),
+            protocol_tag: AlkaneMessageContext::protocol_tag() as u128,
+            pointer: Some(0),
+            refund: Some(0),
+            from: None,
+            burn: None,
+            edicts: vec![],
+        })
+        .collect::<Vec<Protostone>>();
+
+    Ok(Transaction {
+        version: Version::ONE,
+        // Distinct lock times keep otherwise identical calls from sharing a txid
+        lock_time: LockTime::from_consensus(height),
+        input: inputs
+            .into_iter()
+            .map(|previous_output| TxIn {
+                previous_output,
+                script_sig: ScriptBuf::new(),
+                sequence: Sequence::MAX,
+                witness: Witness::new(),
+            })
+            .collect(),
+        output: vec![
+            TxOut {
+                script_pubkey: Address::from_str(ADDRESS1().as_str())
+                    .unwrap()
+                    .require_network(get_btc_network())
+                    .unwrap()
+                    .script_pubkey(),
+                value: Amount::from_sat(546),
+            },
+            TxOut {
+                script_pubkey: (Runestone {
+                    edicts: vec![],
+                    etching: None,
+                    mint: None,
+                    pointer: None,
+                    protocol: Some(protostones.encipher()?),
+                })
+                .encipher(),
+                value: Amount::from_sat(546),
+            },
+        ],
+    })
+}
+
+/// The last trace event of a protostone is its return or revert
+fn read_response(txid: Txid, vout: u32) -> Result<(ExtendedCallResponse, bool)> {
+    let trace_data = &view::trace(&OutPoint { txid, vout })?;
+    let trace: Trace = alkanes_support::proto::alkanes::AlkanesTrace::parse_from_bytes(trace_data)?.into();
+    let events = trace.0.lock().unwrap();
+    match events.last() {
+        Some(TraceEvent::ReturnContext(response)) => Ok((response.inner.clone(), false)),
+        Some(TraceEvent::RevertContext(response)) => Ok((response.inner.clone(), true)),
+        other => Err(anyhow!("No call response traced at {}:{}: {:?}", txid, vout, other)),
+    }
+}
+
+/// Units of `token` held by `outpoint`
+pub fn balance(outpoint: &OutPoint, token: &AlkaneId) -> Result<u128> {
+    let sheet = load_sheet(
+        &RuneTable::for_protocol(AlkaneMessageContext::protocol_tag())
+            .OUTPOINT_TO_RUNES
+            .select(&consensus_encode(outpoint)?),
+    );
+    Ok(sheet.get(&ProtoruneRuneId {
+        block: token.block,
+        tx: token.tx,
+    }))
+}
+
+/// The `index`-th 16-byte little-endian value of `data`
+pub fn u128_at(data: &[u8], index: usize) -> Result<u128> {
+    let bytes = data
+        .get(index * 16..index * 16 + 16)
+        .ok_or_else(|| anyhow!("No u128 at index {} of {} bytes", index, data.len()))?;
+    Ok(u128::from_le_bytes(bytes.try_into()?))
+}
+
+/// AlkaneId encoded as block then tx, starting at byte `offset`
+pub fn id_at(data: &[u8], offset: usize) -> Result<AlkaneId> {
+    let bytes = data
+        .get(offset..offset + 32)
+        .ok_or_else(|| anyhow!("No AlkaneId at offset {} of {} bytes", offset, data.len()))?;
+    Ok(AlkaneId {
+        block: u128_at(bytes, 0)?,
+        tx: u128_at(bytes, 1)?,
+    })
+}
+
+/// `[count (8 bytes)] + [AlkaneId (32 bytes)]*`, starting at byte `offset`
+pub fn id_list_at(data: &[u8], offset: usize) -> Result<Vec<AlkaneId>> {
+    let count_bytes = data
+        .get(offset..offset + 8)
+        .ok_or_else(|| anyhow!("No id count at offset {} of {} bytes", offset, data.len()))?;
+    let count = u64::from_le_bytes(count_bytes.try_into()?) as usize;
+    (0..count).map(|index| id_at(data, offset + 8 + index * 32)).collect()
+}
+
+/// The txid split into the two LE halves GetCouponByTxid takes
+pub fn txid_halves(txid: &Txid) -> [u128; 2] {
+    let bytes = txid.as_byte_array();
+    [
+        u128::from_le_bytes(bytes[..16].try_into().unwrap()),
+        u128::from_le_bytes(bytes[16..].try_into().unwrap()),
+    ]
+}
@@ -0,0 +1,34 @@
+use anyhow::Result;
+use wasm_bindgen_test::wasm_bindgen_test;
+
+use crate::tests::factory_harness::*;
+
+fn initialize(harness: &mut FactoryHarness, success_threshold: u128, template: (u128, u128)) -> Result<Call> {
+    harness.query(vec![0u128, success_threshold, template.0, template.1])
+}
+
+#[wasm_bindgen_test]
+fn test_initialize_rejects_invalid_parameters() -> Result<()> {
+    let mut harness = FactoryHarness::deployed()?;
+    let template = (COUPON_TEMPLATE_ID.block, COUPON_TEMPLATE_ID.tx);
+
+    initialize(&mut harness, 255, template)?
+        .expect_revert("Success threshold must be below 255 so coupons can win, got 255")?;
+    initialize(&mut harness, TEST_THRESHOLD, (0, 0))?
+        .expect_revert("Coupon template ID must not be zero")?;
+    initialize(&mut harness, TEST_THRESHOLD, (FACTORY_ID.block, FACTORY_ID.tx))?
+        .expect_revert("Coupon template ID must not be the factory itself")?;
+    initialize(&mut harness, TEST_THRESHOLD, (4, 0x999))?
+        .expect_revert("Coupon template 4:2457 did not respond")?;
+
+    // Failed attempts leave the factory uninitialized
+    let init = initialize(&mut harness, TEST_THRESHOLD, template)?;
+    assert_eq!(init.returned()?.len(), 4);
+    assert_eq!(harness.query(vec![21u128])?.u128()?, TEST_THRESHOLD);
+    assert_eq!(harness.query(vec![23u128])?.id()?, COUPON_TEMPLATE_ID);
+
+    initialize(&mut harness, 100, template)?.expect_revert("already initialized")?;
+    assert_eq!(harness.query(vec![21u128])?.u128()?, TEST_THRESHOLD);
+
+    Ok(())
+}
@@ -0,0 +1,65 @@
+use alkanes_support::id::AlkaneId;
+use anyhow::Result;
+use wasm_bindgen_test::wasm_bindgen_test;
+
+use crate::tests::factory_harness::*;
+
+#[wasm_bindgen_test]
+fn test_admin_retires_active_coupons() -> Result<()> {
+    let mut harness = FactoryHarness::initialized()?;
+    let first = harness.winning_coupon()?;
+    let second = harness.winning_coupon()?;
+
+    let unknown = harness.role_tokens[0].clone();
+    assert_eq!(harness.coupon_status(&unknown)?, 0);
+    assert_eq!(harness.coupon_status(&first.id)?, COUPON_ACTIVE);
+
+    harness
+        .query(vec![90u128, first.id.block, first.id.tx, COUPON_EXPIRED])?
+        .expect_revert("Auth token for role 0 is required")?;
+    harness
+        .call_with_roles(vec![90u128, first.id.block, first.id.tx, COUPON_REDEEMED])?
+        .expect_revert("Status must be 4 (expired) or 5 (void), got 2")?;
+
+    harness.call_with_roles(vec![90u128, first.id.block, first.id.tx, COUPON_EXPIRED])?.ok()?;
+    harness.call_with_roles(vec![90u128, second.id.block, second.id.tx, COUPON_VOID])?.ok()?;
+    assert_eq!(harness.coupon_status(&first.id)?, COUPON_EXPIRED);
+    assert_eq!(harness.coupon_status(&second.id)?, COUPON_VOID);
+
+    // Only active coupons can change status or be redeemed
+    harness
+        .call_with_roles(vec![90u128, first.id.block, first.id.tx, COUPON_VOID])?
+        .expect_revert("is expired")?;
+    let redeem = harness.call(&[second.outpoint], factory_cellpack(vec![2u128]))?;
+    redeem.expect_revert("is void")?;
+    assert_eq!(balance(&redeem.outpoint, &second.id)?, 1);
+
+    // [total (16)] + [count (8)] + [AlkaneId (32) + status (16)] per coupon
+    let page = harness.query(vec![38u128, 0u128, 10u128])?;
+    let page = page.data()?;
+    assert_eq!(u128_at(page, 0)?, 2);
+    assert_eq!(u64::from_le_bytes(page[16..24].try_into()?), 2);
+    let statuses = (0..2)
+        .map(|index| {
+            let offset = 24 + index * 48;
+            Ok((id_at(page, offset)?, u128_at(&page[offset + 32..], 0)?))
+        })
+        .collect::<Result<Vec<(AlkaneId, u128)>>>()?;
+    assert_eq!(statuses, vec![(first.id, COUPON_EXPIRED), (second.id, COUPON_VOID)]);
+
+    Ok(())
+}
+
+#[wasm_bindgen_test]
+fn test_burned_coupons_stay_with_the_factory() -> Result<()> {
+    let mut harness = FactoryHarness::initialized()?;
+    let coupon = harness.winning_coupon()?;
+
+    let burn = harness.call(&[coupon.outpoint], factory_cellpack(vec![6u128]))?;
+    burn.ok()?;
+    assert_eq!(balance(&burn.outpoint, &coupon.id)?, 0);
+    assert_eq!(harness.coupon_status(&coupon.id)?, COUPON_BURNED);
+    assert_eq!(harness.query(vec![32u128, coupon.id.block, coupon.id.tx])?.u128()?, 0);
+
+    Ok(())
+}
@@ -5,3 +5,21 @@
 // It declares the test modules.
 
 pub mod debug_minimal_test;
+pub mod factory_harness;
+pub mod payout_test;
+pub mod admin_test;
+pub mod pause_test;
+pub mod timelock_test;
+pub mod role_test;
+pub mod schema_test;
+pub mod template_test;
+pub mod config_test;
+pub mod initialize_test;
+pub mod registry_test;
+pub mod stats_test;
+pub mod volume_test;
+pub mod creation_test;
+pub mod lifecycle_test;
+pub mod coupon_test;
+pub mod reroll_test;
+pub mod craft_test;
@@ -0,0 +1,64 @@
+use anyhow::Result;
+use wasm_bindgen_test::wasm_bindgen_test;
+
+use crate::tests::factory_harness::*;
+
+#[wasm_bindgen_test]
+fn test_pause_blocks_coupon_creation() -> Result<()> {
+    let mut harness = FactoryHarness::initialized()?;
+    let (dust, _) = harness.new_token(LOSING_STAKE)?;
+
+    harness.query(vec![77u128])?.expect_revert("Auth token for role 3 is required")?;
+    harness.call_with_roles(vec![77u128])?.ok()?;
+
+    let status = harness.query(vec![41u128])?;
+    assert_eq!(u128_at(status.data()?, 0)?, 1);
+    assert_eq!(String::from_utf8_lossy(&status.data()?[16..]), "Paused by admin");
+
+    // The stake is refunded while paused
+    let stake = harness.mint(&dust)?;
+    let refused = harness.call(&[stake], factory_cellpack(vec![1u128]))?;
+    refused.expect_revert("Factory is paused: Paused by admin")?;
+    assert_eq!(balance(&refused.outpoint, &dust)?, LOSING_STAKE);
+
+    harness.query(vec![78u128])?.expect_revert("Auth token for role 1 is required")?;
+    harness.call_with_roles(vec![78u128])?.ok()?;
+    assert_eq!(u128_at(harness.query(vec![41u128])?.data()?, 0)?, 0);
+
+    harness.create_coupon(refused.outpoint)?;
+    assert_eq!(harness.query(vec![12u128])?.u128()?, 1);
+
+    Ok(())
+}
+
+#[wasm_bindgen_test]
+fn test_circuit_breaker_pauses_on_win_streak() -> Result<()> {
+    let mut harness = FactoryHarness::initialized()?;
+
+    // Pause once more than half of the last two coupons win
+    harness.query(vec![79u128, 2u128, 5_000u128])?.expect_revert("Auth token for role 1 is required")?;
+    harness.call_with_roles(vec![79u128, 2u128, 5_000u128])?.ok()?;
+
+    harness.winning_coupon()?;
+    assert_eq!(u128_at(harness.query(vec![41u128])?.data()?, 0)?, 0);
+
+    let tripping = harness.winning_coupon()?;
+    let breaker = harness.query(vec![42u128])?;
+    let breaker = breaker.data()?;
+    assert_eq!(
+        (0..4).map(|index| u128_at(breaker, index)).collect::<Result<Vec<u128>>>()?,
+        vec![2, 5_000, 2, 2]
+    );
+
+    let status = harness.query(vec![41u128])?;
+    assert_eq!(u128_at(status.data()?, 0)?, 1);
+    let reason = String::from_utf8_lossy(&status.data()?[16..]).to_string();
+    assert!(reason.contains(&format!("Circuit breaker tripped at block {}", tripping.height)));
+
+    let (_, stake) = harness.new_token(WINNING_STAKE)?;
+    harness
+        .call(&[stake], factory_cellpack(vec![1u128]))?
+        .expect_revert("Factory is paused: Circuit breaker tripped")?;
+
+    Ok(())
+}
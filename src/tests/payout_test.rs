@@ -0,0 +1,134 @@
+use anyhow::Result;
+use wasm_bindgen_test::wasm_bindgen_test;
+
+use crate::tests::factory_harness::*;
+
+#[wasm_bindgen_test]
+fn test_redeem_pays_from_prize_inventory() -> Result<()> {
+    let mut harness = FactoryHarness::initialized()?;
+    let (prize, prize_outpoint) = harness.new_token(1_000_000)?;
+
+    // Winners get one prize unit per two staked units
+    harness.call_with_roles(vec![70u128, prize.block, prize.tx, 1u128, 2u128])?.ok()?;
+    harness.call(&[prize_outpoint], factory_cellpack(vec![3u128]))?.ok()?;
+    assert_eq!(harness.query(vec![60u128, prize.block, prize.tx])?.u128()?, 1_000_000);
+
+    let coupon = harness.winning_coupon()?;
+    let redeem = harness.call(&[coupon.outpoint], factory_cellpack(vec![2u128]))?;
+    assert_eq!(redeem.u128()?, WINNING_STAKE / 2);
+    assert_eq!(balance(&redeem.outpoint, &prize)?, WINNING_STAKE / 2);
+    assert_eq!(balance(&redeem.outpoint, &coupon.id)?, 0);
+
+    assert_eq!(
+        harness.query(vec![60u128, prize.block, prize.tx])?.u128()?,
+        1_000_000 - WINNING_STAKE / 2
+    );
+    assert_eq!(harness.coupon_status(&coupon.id)?, COUPON_REDEEMED);
+    assert_eq!(harness.query(vec![32u128, coupon.id.block, coupon.id.tx])?.u128()?, 1);
+
+    Ok(())
+}
+
+#[wasm_bindgen_test]
+fn test_redeem_refuses_payout_beyond_inventory() -> Result<()> {
+    let mut harness = FactoryHarness::initialized()?;
+    let (prize, prize_outpoint) = harness.new_token(1_000)?;
+
+    harness.call_with_roles(vec![70u128, prize.block, prize.tx, 1u128, 1u128])?.ok()?;
+    harness.call(&[prize_outpoint], factory_cellpack(vec![3u128]))?.ok()?;
+
+    let coupon = harness.winning_coupon()?;
+    let redeem = harness.call(&[coupon.outpoint], factory_cellpack(vec![2u128]))?;
+    redeem.expect_revert("Insufficient prize inventory")?;
+
+    // The coupon is refunded and stays redeemable
+    assert_eq!(balance(&redeem.outpoint, &coupon.id)?, 1);
+    assert_eq!(balance(&redeem.outpoint, &prize)?, 0);
+    assert_eq!(harness.coupon_status(&coupon.id)?, COUPON_ACTIVE);
+    assert_eq!(harness.query(vec![60u128, prize.block, prize.tx])?.u128()?, 1_000);
+
+    Ok(())
+}
+
+#[wasm_bindgen_test]
+fn test_treasurer_withdraws_unused_prizes() -> Result<()> {
+    let mut harness = FactoryHarness::initialized()?;
+    let (prize, prize_outpoint) = harness.new_token(1_000)?;
+    harness.call(&[prize_outpoint], factory_cellpack(vec![3u128]))?.ok()?;
+
+    harness
+        .query(vec![69u128, prize.block, prize.tx, 400u128])?
+        .expect_revert("Auth token for role 2 is required")?;
+
+    let (key, key_outpoint) = harness.role_key(ROLE_TREASURER)?;
+    let refused = harness.call(&[key_outpoint], factory_cellpack(vec![69u128, prize.block, prize.tx, 1_001u128]))?;
+    refused.expect_revert("Insufficient prize inventory")?;
+
+    let withdraw = harness.call(&[refused.outpoint], factory_cellpack(vec![69u128, prize.block, prize.tx, 400u128]))?;
+    withdraw.ok()?;
+    assert_eq!(balance(&withdraw.outpoint, &prize)?, 400);
+    assert_eq!(balance(&withdraw.outpoint, &key)?, 1);
+    assert_eq!(harness.query(vec![60u128, prize.block, prize.tx])?.u128()?, 600);
+
+    Ok(())
+}
+
+#[wasm_bindgen_test]
+fn test_losing_coupon_cannot_be_redeemed() -> Result<()> {
+    let mut harness = FactoryHarness::initialized()?;
+    let (dust, _) = harness.new_token(LOSING_STAKE)?;
+
+    let coupon = harness.losing_coupon(&dust)?;
+    let redeem = harness.call(&[coupon.outpoint], factory_cellpack(vec![2u128]))?;
+    redeem.expect_revert("Only winning coupons can be redeemed")?;
+    assert_eq!(balance(&redeem.outpoint, &coupon.id)?, 1);
+
+    Ok(())
+}
+
+#[wasm_bindgen_test]
+fn test_claim_prize_waits_for_tier_inventory() -> Result<()> {
+    let mut harness = FactoryHarness::initialized()?;
+
+    let coupon = harness.winning_coupon()?;
+    let refused = harness.call(&[coupon.outpoint], factory_cellpack(vec![4u128]))?;
+    refused.expect_revert("No prizes left in tier Jackpot")?;
+    assert_eq!(balance(&refused.outpoint, &coupon.id)?, 1);
+    assert_eq!(harness.coupon_status(&coupon.id)?, COUPON_ACTIVE);
+
+    // Deposit one NFT into the Jackpot tier with a treasurer-only key
+    let (key, key_outpoint) = harness.role_key(ROLE_TREASURER)?;
+    let (nft, nft_outpoint) = harness.new_token(1)?;
+    let deposit = harness.call(&[key_outpoint, nft_outpoint], factory_cellpack(vec![71u128, 4u128]))?;
+    deposit.ok()?;
+    assert_eq!(balance(&deposit.outpoint, &key)?, 1);
+    assert_eq!(balance(&deposit.outpoint, &nft)?, 0);
+    assert_eq!(id_list_at(harness.query(vec![62u128, 4u128])?.data()?, 0)?, vec![nft.clone()]);
+
+    let claim = harness.call(&[refused.outpoint], factory_cellpack(vec![4u128]))?;
+    claim.ok()?;
+    assert_eq!(balance(&claim.outpoint, &nft)?, 1);
+    assert_eq!(balance(&claim.outpoint, &coupon.id)?, 0);
+    assert_eq!(harness.coupon_status(&coupon.id)?, COUPON_REDEEMED);
+    assert!(id_list_at(harness.query(vec![62u128, 4u128])?.data()?, 0)?.is_empty());
+
+    Ok(())
+}
+
+#[wasm_bindgen_test]
+fn test_deposit_tier_prize_requires_treasurer() -> Result<()> {
+    let mut harness = FactoryHarness::initialized()?;
+    let (nft, nft_outpoint) = harness.new_token(1)?;
+
+    let deposit = harness.call(&[nft_outpoint], factory_cellpack(vec![71u128, 4u128]))?;
+    deposit.expect_revert("Auth token for role 2 is required")?;
+    assert_eq!(balance(&deposit.outpoint, &nft)?, 1);
+
+    // Losing coupons have no prize tier
+    let (_, key_outpoint) = harness.role_key(ROLE_TREASURER)?;
+    let lose_tier = harness.call(&[key_outpoint, deposit.outpoint], factory_cellpack(vec![71u128, 0u128]))?;
+    lose_tier.expect_revert("Prize tier must be between 1 and 4")?;
+    assert_eq!(balance(&lose_tier.outpoint, &nft)?, 1);
+
+    Ok(())
+}
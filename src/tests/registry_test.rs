@@ -0,0 +1,65 @@
+use alkanes_support::id::AlkaneId;
+use anyhow::Result;
+use bitcoin::hashes::Hash;
+use wasm_bindgen_test::wasm_bindgen_test;
+
+use crate::tests::factory_harness::*;
+
+fn page(harness: &mut FactoryHarness, offset: u128, limit: u128) -> Result<(u128, Vec<AlkaneId>)> {
+    let call = harness.query(vec![33u128, offset, limit])?;
+    Ok((u128_at(call.data()?, 0)?, id_list_at(call.data()?, 16)?))
+}
+
+#[wasm_bindgen_test]
+fn test_registry_pages_in_mint_order() -> Result<()> {
+    let mut harness = FactoryHarness::initialized()?;
+    let coupons = (0..3)
+        .map(|_| Ok(harness.winning_coupon()?.id))
+        .collect::<Result<Vec<AlkaneId>>>()?;
+
+    assert_eq!(page(&mut harness, 0, 2)?, (3, coupons[..2].to_vec()));
+    assert_eq!(page(&mut harness, 2, 2)?, (3, coupons[2..].to_vec()));
+    assert_eq!(page(&mut harness, 3, 2)?, (3, vec![]));
+    assert_eq!(id_list_at(harness.query(vec![30u128])?.data()?, 0)?, coupons);
+
+    for (index, coupon) in coupons.iter().enumerate() {
+        assert_eq!(harness.query(vec![34u128, coupon.block, coupon.tx])?.u128()?, index as u128);
+        assert_eq!(harness.query(vec![31u128, coupon.block, coupon.tx])?.u128()?, 1);
+    }
+
+    let unknown = harness.role_tokens[0].clone();
+    assert_eq!(harness.query(vec![31u128, unknown.block, unknown.tx])?.u128()?, 0);
+    harness
+        .query(vec![34u128, unknown.block, unknown.tx])?
+        .expect_revert("is not registered")?;
+
+    Ok(())
+}
+
+#[wasm_bindgen_test]
+fn test_coupons_are_found_by_creation_txid() -> Result<()> {
+    let mut harness = FactoryHarness::initialized()?;
+    let first = harness.winning_coupon()?;
+    let second = harness.winning_coupon()?;
+
+    for coupon in [&first, &second] {
+        let [txid_lo, txid_hi] = txid_halves(&coupon.txid);
+        assert_eq!(
+            id_list_at(harness.query(vec![35u128, txid_lo, txid_hi])?.data()?, 0)?,
+            vec![coupon.id.clone()]
+        );
+        assert_eq!(
+            harness.query(vec![36u128, coupon.id.block, coupon.id.tx])?.data()?,
+            coupon.txid.as_byte_array().as_slice()
+        );
+    }
+
+    // Transactions that minted nothing list no coupons
+    assert!(id_list_at(harness.query(vec![35u128, 1u128, 2u128])?.data()?, 0)?.is_empty());
+    let unknown = harness.role_tokens[0].clone();
+    harness
+        .query(vec![36u128, unknown.block, unknown.tx])?
+        .expect_revert("No creation txid recorded")?;
+
+    Ok(())
+}
@@ -0,0 +1,87 @@
+use anyhow::Result;
+use wasm_bindgen_test::wasm_bindgen_test;
+
+use crate::tests::factory_harness::*;
+
+/// Long enough for a few losing rolls before the change activates
+const REROLL_TIMELOCK: u32 = 50;
+
+#[wasm_bindgen_test]
+fn test_reroll_replaces_a_losing_coupon() -> Result<()> {
+    let mut harness = FactoryHarness::initialized()?;
+    let (dust, _) = harness.new_token(LOSING_STAKE)?;
+    let parent = harness.losing_coupon(&dust)?;
+
+    let stake = harness.mint(&dust)?;
+    let reroll = harness.call(&[parent.outpoint, stake], factory_cellpack(vec![7u128]))?;
+    let child = reroll.returned()?[0].id.clone();
+    assert_eq!(balance(&reroll.outpoint, &child)?, 1);
+    assert_eq!(balance(&reroll.outpoint, &parent.id)?, 0);
+
+    assert_eq!(harness.coupon_status(&parent.id)?, COUPON_BURNED);
+    assert_eq!(harness.coupon_status(&child)?, COUPON_ACTIVE);
+    assert_eq!(harness.query(vec![39u128, child.block, child.tx])?.id()?, parent.id);
+    assert_eq!(harness.query_coupon(&child, vec![22u128])?.id()?, parent.id);
+
+    // Stakes under 1000 earn no bonus, so the default re-roll bonus is all of it
+    assert_eq!(harness.query_coupon(&child, vec![13u128])?.u128()?, 10);
+
+    Ok(())
+}
+
+#[wasm_bindgen_test]
+fn test_reroll_refuses_winning_coupons() -> Result<()> {
+    let mut harness = FactoryHarness::initialized()?;
+    let winner = harness.winning_coupon()?;
+    let (dust, stake) = harness.new_token(LOSING_STAKE)?;
+
+    let reroll = harness.call(&[winner.outpoint, stake], factory_cellpack(vec![7u128]))?;
+    reroll.expect_revert("is a winner, only losing coupons can be consumed")?;
+    assert_eq!(balance(&reroll.outpoint, &winner.id)?, 1);
+    assert_eq!(balance(&reroll.outpoint, &dust)?, LOSING_STAKE);
+    assert_eq!(harness.coupon_status(&winner.id)?, COUPON_ACTIVE);
+
+    Ok(())
+}
+
+#[wasm_bindgen_test]
+fn test_reroll_requires_a_new_stake() -> Result<()> {
+    let mut harness = FactoryHarness::initialized()?;
+    let (dust, _) = harness.new_token(LOSING_STAKE)?;
+    let parent = harness.losing_coupon(&dust)?;
+
+    let reroll = harness.call(&[parent.outpoint], factory_cellpack(vec![7u128]))?;
+    reroll.expect_revert("A re-roll needs a new stake alongside the coupon")?;
+    assert_eq!(balance(&reroll.outpoint, &parent.id)?, 1);
+    assert_eq!(harness.coupon_status(&parent.id)?, COUPON_ACTIVE);
+
+    Ok(())
+}
+
+#[wasm_bindgen_test]
+fn test_reroll_bonus_change_waits_for_timelock() -> Result<()> {
+    let mut harness = FactoryHarness::initialized()?;
+    harness.call_with_roles(vec![81u128, REROLL_TIMELOCK as u128])?.ok()?;
+
+    harness.query(vec![92u128, 30u128])?.expect_revert("Auth token for role 1 is required")?;
+    let scheduled = harness.call_with_roles(vec![92u128, 30u128])?;
+    scheduled.ok()?;
+
+    // Re-rolls before the activation block still get the old bonus
+    let (dust, _) = harness.new_token(LOSING_STAKE)?;
+    let parent = harness.losing_coupon(&dust)?;
+    let stake = harness.mint(&dust)?;
+    assert!(harness.next_height() < scheduled.height + REROLL_TIMELOCK);
+    let reroll = harness.call(&[parent.outpoint, stake], factory_cellpack(vec![7u128]))?;
+    let child = reroll.returned()?[0].id.clone();
+    assert_eq!(harness.query_coupon(&child, vec![13u128])?.u128()?, 10);
+
+    harness.advance_to(scheduled.height + REROLL_TIMELOCK);
+    let parent = harness.losing_coupon(&dust)?;
+    let stake = harness.mint(&dust)?;
+    let reroll = harness.call(&[parent.outpoint, stake], factory_cellpack(vec![7u128]))?;
+    let child = reroll.returned()?[0].id.clone();
+    assert_eq!(harness.query_coupon(&child, vec![13u128])?.u128()?, 30);
+
+    Ok(())
+}
@@ -0,0 +1,63 @@
+use anyhow::Result;
+use wasm_bindgen_test::wasm_bindgen_test;
+
+use crate::tests::factory_harness::*;
+
+#[wasm_bindgen_test]
+fn test_role_tokens_only_unlock_their_role() -> Result<()> {
+    let mut harness = FactoryHarness::initialized()?;
+    for role in [ROLE_ADMIN, ROLE_OPERATOR, ROLE_TREASURER, ROLE_PAUSER] {
+        let tokens = id_list_at(harness.query(vec![57u128, role])?.data()?, 0)?;
+        assert_eq!(tokens, vec![harness.role_tokens[role as usize].clone()]);
+    }
+
+    let (key, key_outpoint) = harness.role_key(ROLE_OPERATOR)?;
+
+    // Operator opcodes accept the key, the other roles' opcodes do not
+    let fee = harness.call(&[key_outpoint], factory_cellpack(vec![80u128, 100u128]))?;
+    fee.ok()?;
+    assert_eq!(balance(&fee.outpoint, &key)?, 1);
+    assert_eq!(harness.query(vec![54u128])?.u128()?, 100);
+
+    let pause = harness.call(&[fee.outpoint], factory_cellpack(vec![77u128]))?;
+    pause.expect_revert("Auth token for role 3 is required")?;
+    let withdraw = harness.call(&[pause.outpoint], factory_cellpack(vec![83u128, 2u128, 1u128]))?;
+    withdraw.expect_revert("Auth token for role 2 is required")?;
+    let grant = harness.call(&[withdraw.outpoint], factory_cellpack(vec![84u128, ROLE_PAUSER]))?;
+    grant.expect_revert("Auth token for role 0 is required")?;
+    assert_eq!(balance(&grant.outpoint, &key)?, 1);
+
+    // Once revoked, the key no longer counts as an operator
+    harness.call_with_roles(vec![86u128, ROLE_OPERATOR, key.block, key.tx])?.ok()?;
+    harness
+        .call(&[grant.outpoint], factory_cellpack(vec![80u128, 200u128]))?
+        .expect_revert("Auth token for role 1 is required")?;
+    assert_eq!(harness.query(vec![54u128])?.u128()?, 100);
+
+    Ok(())
+}
+
+#[wasm_bindgen_test]
+fn test_grant_role_mints_a_new_holder() -> Result<()> {
+    let mut harness = FactoryHarness::initialized()?;
+
+    let grant = harness.call_with_roles(vec![84u128, ROLE_PAUSER])?;
+    let granted = grant
+        .returned()?
+        .into_iter()
+        .map(|transfer| transfer.id)
+        .find(|id| !harness.role_tokens.contains(id))
+        .expect("GrantRole returns a fresh token");
+    assert_eq!(balance(&grant.outpoint, &granted)?, 1);
+
+    let tokens = id_list_at(harness.query(vec![57u128, ROLE_PAUSER])?.data()?, 0)?;
+    assert_eq!(tokens, vec![harness.role_tokens[ROLE_PAUSER as usize].clone(), granted]);
+
+    // The last admin token cannot be revoked
+    let admin = harness.role_tokens[ROLE_ADMIN as usize].clone();
+    harness
+        .call_with_roles(vec![86u128, ROLE_ADMIN, admin.block, admin.tx])?
+        .expect_revert("Cannot revoke the last admin token")?;
+
+    Ok(())
+}
@@ -0,0 +1,40 @@
+use anyhow::Result;
+use wasm_bindgen_test::wasm_bindgen_test;
+
+use crate::tests::factory_harness::*;
+
+/// Schema version written by Initialize
+const CURRENT_SCHEMA_VERSION: u128 = 1;
+
+#[wasm_bindgen_test]
+fn test_migrate_is_a_no_op_on_the_current_layout() -> Result<()> {
+    let mut harness = FactoryHarness::initialized()?;
+    assert_eq!(harness.query(vec![58u128])?.u128()?, CURRENT_SCHEMA_VERSION);
+
+    harness.query(vec![87u128])?.expect_revert("Auth token for role 0 is required")?;
+
+    // Migrate reports how many registered coupons are still waiting to move
+    assert_eq!(harness.call_with_roles(vec![87u128])?.u128()?, 0);
+    assert_eq!(harness.query(vec![58u128])?.u128()?, CURRENT_SCHEMA_VERSION);
+
+    // Coupon creation still works after the no-op migration
+    harness.winning_coupon()?;
+    assert_eq!(harness.query(vec![12u128])?.u128()?, 1);
+
+    Ok(())
+}
+
+#[wasm_bindgen_test]
+fn test_migrate_refuses_an_uninitialized_factory() -> Result<()> {
+    let mut harness = FactoryHarness::deployed()?;
+
+    // Nobody can take the factory over before Initialize runs
+    harness.query(vec![87u128])?.expect_revert("Factory is not initialized")?;
+    assert_eq!(harness.query(vec![58u128])?.u128()?, 0);
+
+    let init = harness.query(vec![0u128, TEST_THRESHOLD, COUPON_TEMPLATE_ID.block, COUPON_TEMPLATE_ID.tx])?;
+    assert_eq!(init.returned()?.len(), 4);
+    assert_eq!(harness.query(vec![58u128])?.u128()?, CURRENT_SCHEMA_VERSION);
+
+    Ok(())
+}
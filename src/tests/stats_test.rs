@@ -0,0 +1,60 @@
+use anyhow::Result;
+use wasm_bindgen_test::wasm_bindgen_test;
+
+use crate::tests::factory_harness::*;
+
+fn fields(call: &Call, count: usize) -> Result<Vec<u128>> {
+    (0..count).map(|index| u128_at(call.data()?, index)).collect()
+}
+
+#[wasm_bindgen_test]
+fn test_block_and_epoch_stats_add_up() -> Result<()> {
+    let mut harness = FactoryHarness::initialized()?;
+    let (dust, _) = harness.new_token(LOSING_STAKE)?;
+
+    let winner = harness.winning_coupon()?;
+    let loser = harness.losing_coupon(&dust)?;
+    let loser_result = harness.query_coupon(&loser.id, vec![14u128])?.u128()?;
+
+    // [coupons, wins, total_stake, bonus_sum, highest_final_result]
+    let winner_block = harness.query(vec![13u128, winner.height as u128])?;
+    assert_eq!(fields(&winner_block, 5)?, vec![1, 1, WINNING_STAKE, 255, 255]);
+    let loser_block = harness.query(vec![13u128, loser.height as u128])?;
+    assert_eq!(fields(&loser_block, 5)?, vec![1, 0, LOSING_STAKE, 0, loser_result]);
+
+    // losing_coupon may have set winners aside on the way
+    let total = harness.query(vec![12u128])?.u128()?;
+    let wins = harness.query(vec![10u128])?.u128()?;
+    assert_eq!(harness.query(vec![11u128])?.u128()?, total - wins);
+
+    let epoch = harness.query(vec![14u128, 0u128])?;
+    assert_eq!(fields(&epoch, 2)?, vec![total, wins]);
+    assert_eq!(u128_at(epoch.data()?, 4)?, 255);
+    assert_eq!(fields(&harness.query(vec![14u128, 1u128])?, 5)?, vec![0; 5]);
+
+    Ok(())
+}
+
+#[wasm_bindgen_test]
+fn test_epoch_length_applies_from_the_next_epoch() -> Result<()> {
+    let mut harness = FactoryHarness::initialized()?;
+    assert_eq!(fields(&harness.query(vec![15u128])?, 3)?, vec![0, 0, 144]);
+
+    harness.query(vec![89u128, 10u128])?.expect_revert("Auth token for role 1 is required")?;
+    harness.call_with_roles(vec![89u128, 0u128])?.expect_revert("Epoch length must be at least one block")?;
+    harness.call_with_roles(vec![89u128, 10u128])?.ok()?;
+
+    // The running epoch keeps its length
+    assert_eq!(fields(&harness.query(vec![15u128])?, 2)?, vec![0, 0]);
+
+    harness.advance_to(144);
+    assert_eq!(fields(&harness.query(vec![15u128])?, 3)?, vec![1, 144, 10]);
+    let coupon = harness.winning_coupon()?;
+    assert!(coupon.height < 154);
+    assert_eq!(u128_at(harness.query(vec![14u128, 1u128])?.data()?, 0)?, 1);
+
+    harness.advance_to(154);
+    assert_eq!(fields(&harness.query(vec![15u128])?, 3)?, vec![2, 154, 10]);
+
+    Ok(())
+}
@@ -0,0 +1,65 @@
+use anyhow::Result;
+use wasm_bindgen_test::wasm_bindgen_test;
+
+use crate::tests::factory_harness::*;
+
+#[wasm_bindgen_test]
+fn test_coupons_use_the_configured_template() -> Result<()> {
+    let mut harness = FactoryHarness::initialized()?;
+    assert_eq!(harness.query(vec![23u128])?.id()?, COUPON_TEMPLATE_ID);
+
+    harness
+        .query(vec![75u128, ALT_COUPON_TEMPLATE_ID.block, ALT_COUPON_TEMPLATE_ID.tx])?
+        .expect_revert("Auth token for role 1 is required")?;
+    harness
+        .call_with_roles(vec![75u128, FACTORY_ID.block, FACTORY_ID.tx])?
+        .expect_revert("Coupon template ID must not be the factory itself")?;
+    harness
+        .call_with_roles(vec![75u128, 4u128, 0x999u128])?
+        .expect_revert("Coupon template 4:2457 did not respond")?;
+    assert_eq!(harness.query(vec![23u128])?.id()?, COUPON_TEMPLATE_ID);
+
+    harness
+        .call_with_roles(vec![75u128, ALT_COUPON_TEMPLATE_ID.block, ALT_COUPON_TEMPLATE_ID.tx])?
+        .ok()?;
+    assert_eq!(harness.query(vec![23u128])?.id()?, ALT_COUPON_TEMPLATE_ID);
+
+    // Coupons minted from the new template still report this factory
+    let coupon = harness.winning_coupon()?;
+    assert_eq!(harness.query_coupon(&coupon.id, vec![16u128])?.id()?, FACTORY_ID);
+    assert_eq!(harness.query(vec![31u128, coupon.id.block, coupon.id.tx])?.u128()?, 1);
+
+    Ok(())
+}
+
+#[wasm_bindgen_test]
+fn test_tier_templates_fall_back_to_the_default() -> Result<()> {
+    let mut harness = FactoryHarness::initialized()?;
+    for tier in 0..=4u128 {
+        assert_eq!(harness.query(vec![24u128, tier])?.id()?, COUPON_TEMPLATE_ID);
+    }
+
+    harness
+        .call_with_roles(vec![88u128, 4u128, ALT_COUPON_TEMPLATE_ID.block, ALT_COUPON_TEMPLATE_ID.tx])?
+        .ok()?;
+    assert_eq!(harness.query(vec![24u128, 4u128])?.id()?, ALT_COUPON_TEMPLATE_ID);
+    assert_eq!(harness.query(vec![24u128, 3u128])?.id()?, COUPON_TEMPLATE_ID);
+
+    // Jackpot coupons now come from the tier template
+    let coupon = harness.winning_coupon()?;
+    assert_eq!(harness.query_coupon(&coupon.id, vec![16u128])?.id()?, FACTORY_ID);
+
+    harness
+        .call_with_roles(vec![88u128, 5u128, ALT_COUPON_TEMPLATE_ID.block, ALT_COUPON_TEMPLATE_ID.tx])?
+        .expect_revert("Tier must be at most 4, got 5")?;
+    harness
+        .call_with_roles(vec![88u128, 1u128, FACTORY_ID.block, FACTORY_ID.tx])?
+        .expect_revert("Coupon template ID must not be the factory itself")?;
+    assert_eq!(harness.query(vec![24u128, 1u128])?.id()?, COUPON_TEMPLATE_ID);
+
+    // 0:0 removes the tier template again
+    harness.call_with_roles(vec![88u128, 4u128, 0u128, 0u128])?.ok()?;
+    assert_eq!(harness.query(vec![24u128, 4u128])?.id()?, COUPON_TEMPLATE_ID);
+
+    Ok(())
+}
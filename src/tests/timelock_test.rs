@@ -0,0 +1,111 @@
+use anyhow::Result;
+use wasm_bindgen_test::wasm_bindgen_test;
+
+use crate::tests::factory_harness::*;
+
+const TIMELOCK: u32 = 10;
+
+#[wasm_bindgen_test]
+fn test_scheduled_change_waits_for_activation_block() -> Result<()> {
+    let mut harness = FactoryHarness::initialized()?;
+
+    // Set without a timelock in place, so it applies at once
+    harness.call_with_roles(vec![81u128, TIMELOCK as u128])?.ok()?;
+    assert_eq!(harness.query(vec![55u128])?.u128()?, TIMELOCK as u128);
+
+    let scheduled = harness.call_with_roles(vec![73u128, 100u128])?;
+    let change_id = scheduled.u128()?;
+    let activation = scheduled.height + TIMELOCK;
+
+    let pending = harness.query(vec![56u128])?;
+    let pending = pending.data()?;
+    assert_eq!(u64::from_le_bytes(pending[..8].try_into()?), 1);
+    assert_eq!(
+        (0..5).map(|index| u128_at(&pending[8..], index)).collect::<Result<Vec<u128>>>()?,
+        vec![change_id, 1, activation as u128, 1, 100]
+    );
+
+    assert_eq!(harness.query(vec![21u128])?.u128()?, TEST_THRESHOLD);
+    harness.advance_to(activation - 1);
+    assert_eq!(harness.query(vec![21u128])?.u128()?, TEST_THRESHOLD);
+
+    assert_eq!(harness.next_height(), activation);
+    assert_eq!(harness.query(vec![21u128])?.u128()?, 100);
+    assert_eq!(u64::from_le_bytes(harness.query(vec![56u128])?.data()?[..8].try_into()?), 0);
+
+    Ok(())
+}
+
+#[wasm_bindgen_test]
+fn test_cancelled_change_is_never_applied() -> Result<()> {
+    let mut harness = FactoryHarness::initialized()?;
+    harness.call_with_roles(vec![81u128, TIMELOCK as u128])?.ok()?;
+
+    let scheduled = harness.call_with_roles(vec![80u128, 500u128])?;
+    let change_id = scheduled.u128()?;
+
+    harness.query(vec![82u128, change_id])?.expect_revert("Auth token for role 1 is required")?;
+    harness.call_with_roles(vec![82u128, change_id])?.ok()?;
+    harness
+        .call_with_roles(vec![82u128, change_id])?
+        .expect_revert("is no longer pending")?;
+
+    harness.advance_to(scheduled.height + TIMELOCK);
+    assert_eq!(harness.query(vec![54u128])?.u128()?, 0);
+    assert_eq!(u64::from_le_bytes(harness.query(vec![56u128])?.data()?[..8].try_into()?), 0);
+
+    Ok(())
+}
+
+#[wasm_bindgen_test]
+fn test_payout_change_waits_for_activation_block() -> Result<()> {
+    let mut harness = FactoryHarness::initialized()?;
+    let (prize, prize_outpoint) = harness.new_token(1_000_000)?;
+    harness.call_with_roles(vec![70u128, prize.block, prize.tx, 1u128, 1u128])?.ok()?;
+    harness.call(&[prize_outpoint], factory_cellpack(vec![3u128]))?.ok()?;
+    harness.call_with_roles(vec![81u128, TIMELOCK as u128])?.ok()?;
+
+    // Cutting the ratio after a win does not touch the winner's payout
+    let coupon = harness.winning_coupon()?;
+    let scheduled = harness.call_with_roles(vec![70u128, prize.block, prize.tx, 1u128, 1_000u128])?;
+    let activation = scheduled.height + TIMELOCK;
+    assert_eq!(u128_at(harness.query(vec![61u128])?.data()?, 3)?, 1);
+
+    let redeem = harness.call(&[coupon.outpoint], factory_cellpack(vec![2u128]))?;
+    assert_eq!(redeem.u128()?, WINNING_STAKE);
+
+    harness.advance_to(activation);
+    assert_eq!(u128_at(harness.query(vec![61u128])?.data()?, 3)?, 1_000);
+
+    Ok(())
+}
+
+#[wasm_bindgen_test]
+fn test_tier_boundary_change_waits_for_activation_block() -> Result<()> {
+    let mut harness = FactoryHarness::initialized()?;
+    harness.call_with_roles(vec![81u128, TIMELOCK as u128])?.ok()?;
+
+    let scheduled = harness.call_with_roles(vec![91u128, 180u128, 210u128, 240u128, 255u128])?;
+    let change_id = scheduled.u128()?;
+    let activation = scheduled.height + TIMELOCK;
+
+    let pending = harness.query(vec![56u128])?;
+    let pending = pending.data()?;
+    assert_eq!(u64::from_le_bytes(pending[..8].try_into()?), 1);
+    assert_eq!(
+        (0..8).map(|index| u128_at(&pending[8..], index)).collect::<Result<Vec<u128>>>()?,
+        vec![change_id, 7, activation as u128, 4, 180, 210, 240, 255]
+    );
+    assert_eq!(u128_at(harness.query(vec![25u128])?.data()?, 0)?, 170);
+
+    // Getters report the change once due, before any mutating call writes it back
+    harness.advance_to(activation);
+    let boundaries = harness.query(vec![25u128])?;
+    assert_eq!(
+        (0..4).map(|index| u128_at(boundaries.data()?, index)).collect::<Result<Vec<u128>>>()?,
+        vec![180, 210, 240, 255]
+    );
+    assert_eq!(u64::from_le_bytes(harness.query(vec![56u128])?.data()?[..8].try_into()?), 0);
+
+    Ok(())
+}
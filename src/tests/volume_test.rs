@@ -0,0 +1,70 @@
+use alkanes_support::id::AlkaneId;
+use anyhow::Result;
+use wasm_bindgen_test::wasm_bindgen_test;
+
+use crate::tests::factory_harness::*;
+
+/// [staked, burned, treasury, paid_out]
+fn volume(harness: &mut FactoryHarness, token: &AlkaneId) -> Result<Vec<u128>> {
+    let call = harness.query(vec![16u128, token.block, token.tx])?;
+    (0..4).map(|index| u128_at(call.data()?, index)).collect()
+}
+
+#[wasm_bindgen_test]
+fn test_volume_counters_track_stakes_fees_and_payouts() -> Result<()> {
+    let mut harness = FactoryHarness::initialized()?;
+    harness.call_with_roles(vec![80u128, 1_000u128])?.ok()?;
+
+    let (dust, first_stake) = harness.new_token(LOSING_STAKE)?;
+    harness.create_coupon(first_stake)?;
+    let second_stake = harness.mint(&dust)?;
+    harness.create_coupon(second_stake)?;
+
+    // 10% of each stake goes to the treasury, the rest is burned
+    assert_eq!(volume(&mut harness, &dust)?, vec![1_000, 900, 100, 0]);
+    assert_eq!(harness.query(vec![63u128, dust.block, dust.tx])?.u128()?, 100);
+
+    // Winners are paid 1:1 in their stake token from deposited inventory
+    let (gold, deposit) = harness.new_token(WINNING_STAKE)?;
+    harness.call(&[deposit], factory_cellpack(vec![3u128]))?.ok()?;
+    let stake = harness.mint(&gold)?;
+    let coupon = harness.create_coupon(stake)?;
+    let redeem = harness.call(&[coupon.outpoint], factory_cellpack(vec![2u128]))?;
+    assert_eq!(redeem.u128()?, WINNING_STAKE);
+    assert_eq!(balance(&redeem.outpoint, &gold)?, WINNING_STAKE);
+    assert_eq!(
+        volume(&mut harness, &gold)?,
+        vec![WINNING_STAKE, WINNING_STAKE * 9 / 10, WINNING_STAKE / 10, WINNING_STAKE]
+    );
+
+    assert_eq!(
+        id_list_at(harness.query(vec![17u128])?.data()?, 0)?,
+        vec![dust.clone(), gold.clone()]
+    );
+
+    Ok(())
+}
+
+#[wasm_bindgen_test]
+fn test_withdraw_fees_keeps_cumulative_counters() -> Result<()> {
+    let mut harness = FactoryHarness::initialized()?;
+    harness.call_with_roles(vec![80u128, 1_000u128])?.ok()?;
+
+    let (dust, stake) = harness.new_token(LOSING_STAKE)?;
+    harness.create_coupon(stake)?;
+
+    harness
+        .query(vec![83u128, dust.block, dust.tx])?
+        .expect_revert("Auth token for role 2 is required")?;
+    let withdraw = harness.call_with_roles(vec![83u128, dust.block, dust.tx])?;
+    withdraw.ok()?;
+    assert_eq!(balance(&withdraw.outpoint, &dust)?, 50);
+
+    assert_eq!(harness.query(vec![63u128, dust.block, dust.tx])?.u128()?, 0);
+    assert_eq!(volume(&mut harness, &dust)?, vec![500, 450, 50, 0]);
+    harness
+        .call_with_roles(vec![83u128, dust.block, dust.tx])?
+        .expect_revert("No fees accrued")?;
+
+    Ok(())
+}